@@ -0,0 +1,303 @@
+use regex::Regex;
+use std::collections::BTreeMap;
+
+use crate::discovery::{self, Project};
+use crate::logging::Log;
+use crate::persistence::{HotbarData, SessionMetadata};
+
+pub const SLOT_RULE_PREFIX: &str = "slot_";
+pub const AUTO_FILL_KEY: &str = "auto_fill";
+
+#[derive(Default, PartialEq)]
+pub enum AutoFill {
+    #[default]
+    Off,
+    /// Fill remaining empty slots with the most frequently and recently used sessions
+    Frecency,
+}
+
+#[derive(Default)]
+pub struct AutoAssignConfig {
+    pub slot_patterns: Vec<Option<Regex>>,
+    pub auto_fill: AutoFill,
+}
+
+impl AutoAssignConfig {
+    pub fn from_configuration(configuration: &BTreeMap<String, String>, log: &mut Log) -> Self {
+        let mut slot_patterns: Vec<Option<Regex>> = vec![None; 5];
+
+        for (key, value) in configuration {
+            let Some(slot_str) = key.strip_prefix(SLOT_RULE_PREFIX) else {
+                continue;
+            };
+            let Ok(slot @ 1..=5) = slot_str.parse::<usize>() else {
+                continue;
+            };
+            match Regex::new(value) {
+                Ok(pattern) => slot_patterns[slot - 1] = Some(pattern),
+                Err(e) => log.warn(format!("Invalid pattern for {}: {}", key, e)),
+            }
+        }
+
+        let auto_fill = match configuration.get(AUTO_FILL_KEY).map(String::as_str) {
+            Some("frecency") => AutoFill::Frecency,
+            _ => AutoFill::Off,
+        };
+
+        Self {
+            slot_patterns,
+            auto_fill,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.auto_fill != AutoFill::Off || self.slot_patterns.iter().any(Option::is_some)
+    }
+
+    /// Fills empty slots from the rules. Slots the user assigned, cleared or
+    /// locked are never touched; slots filled by a previous run are refreshed when their
+    /// session is gone. `projects` resolve slots that follow a repository's
+    /// main worktree, so its checkout isn't assigned a second time. Returns
+    /// whether `data` changed.
+    pub fn apply(
+        &self,
+        data: &mut HotbarData,
+        sessions: &[String],
+        projects: &[Project],
+        now: u64,
+    ) -> bool {
+        let mut changed = false;
+
+        for slot in 0..5 {
+            let stale = data.is_auto_assigned(slot)
+                && !data.is_locked(slot)
+                && data
+                    .get_session_at_slot(slot)
+                    .is_some_and(|session| !sessions.contains(session));
+            if stale {
+                data.set_auto_session_at_slot(slot, None);
+                changed = true;
+            }
+        }
+
+        for (slot, pattern) in self.slot_patterns.iter().enumerate() {
+            let Some(pattern) = pattern else {
+                continue;
+            };
+            if data.get_session_at_slot(slot).is_some()
+                || data.is_locked(slot)
+                || data.is_cleared(slot)
+            {
+                continue;
+            }
+            let candidate = sessions
+                .iter()
+                .find(|session| pattern.is_match(session) && !is_assigned(data, projects, session));
+            if let Some(session) = candidate {
+                data.set_auto_session_at_slot(slot, Some(session.clone()));
+                changed = true;
+            }
+        }
+
+        if self.auto_fill == AutoFill::Frecency {
+            let mut ranked: Vec<(&String, f64)> = sessions
+                .iter()
+                .filter_map(|session| {
+                    let metadata = data.get_metadata(session)?;
+                    Some((session, frecency(metadata, now)))
+                })
+                .collect();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            let mut ranked = ranked.into_iter().map(|(session, _)| session);
+            for slot in 0..5 {
+                // Slots with a rule are reserved for sessions matching it
+                if data.get_session_at_slot(slot).is_some()
+                    || data.is_locked(slot)
+                    || data.is_cleared(slot)
+                    || self.slot_patterns[slot].is_some()
+                {
+                    continue;
+                }
+                let Some(session) = ranked.find(|session| !is_assigned(data, projects, session))
+                else {
+                    break;
+                };
+                data.set_auto_session_at_slot(slot, Some(session.clone()));
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
+
+fn is_assigned(data: &HotbarData, projects: &[Project], session: &str) -> bool {
+    data.favorites.iter().flatten().any(|reference| {
+        match discovery::main_worktree_repo(reference) {
+            Some(repo) => discovery::main_worktree_of(projects, repo)
+                .is_some_and(|project| project.name == session),
+            None => reference == session,
+        }
+    })
+}
+
+// Access count weighted by how recently the session was used, in the spirit
+// of zoxide's frecency
+fn frecency(metadata: &SessionMetadata, now: u64) -> f64 {
+    let age = now.saturating_sub(metadata.last_accessed);
+    let weight = match age {
+        0..3_600 => 4.0,
+        3_600..86_400 => 2.0,
+        86_400..604_800 => 0.5,
+        _ => 0.25,
+    };
+    metadata.access_count as f64 * weight
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sessions(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn accessed(data: &mut HotbarData, session: &str, last_accessed: u64, access_count: u64) {
+        data.metadata.insert(
+            session.to_string(),
+            SessionMetadata {
+                last_accessed,
+                access_count,
+                ..Default::default()
+            },
+        );
+    }
+
+    fn slots(data: &HotbarData) -> Vec<Option<&str>> {
+        data.favorites.iter().map(Option::as_deref).collect()
+    }
+
+    #[test]
+    fn never_fills_locked_slots() {
+        let config = AutoAssignConfig {
+            slot_patterns: vec![Some(Regex::new("^api").unwrap()), None, None, None, None],
+            auto_fill: AutoFill::Frecency,
+        };
+        let mut data = HotbarData {
+            locked: vec![true; 5],
+            ..Default::default()
+        };
+        accessed(&mut data, "docs", 0, 1);
+
+        assert!(!config.apply(&mut data, &sessions(&["api", "docs"]), &[], 0));
+        assert_eq!(slots(&data), [None; 5]);
+    }
+
+    #[test]
+    fn slots_with_a_rule_are_only_filled_by_it() {
+        let config = AutoAssignConfig {
+            slot_patterns: vec![Some(Regex::new("^web-").unwrap()), None, None, None, None],
+            auto_fill: AutoFill::Frecency,
+        };
+        let mut data = HotbarData::default();
+        accessed(&mut data, "api", 0, 10);
+
+        config.apply(&mut data, &sessions(&["api"]), &[], 0);
+        assert_eq!(slots(&data), [None, Some("api"), None, None, None]);
+
+        config.apply(&mut data, &sessions(&["api", "web-shop"]), &[], 0);
+        assert_eq!(slots(&data)[0], Some("web-shop"));
+    }
+
+    #[test]
+    fn refreshes_auto_assigned_slots_whose_session_is_gone() {
+        let config = AutoAssignConfig {
+            slot_patterns: vec![Some(Regex::new("^api").unwrap()), None, None, None, None],
+            auto_fill: AutoFill::Off,
+        };
+        let mut data = HotbarData::default();
+        data.set_auto_session_at_slot(0, Some("api-old".to_string()));
+        // Slots the user assigned stay, session or not
+        data.set_session_at_slot(1, Some("gone".to_string()));
+
+        assert!(config.apply(&mut data, &sessions(&["api-new"]), &[], 0));
+        assert_eq!(slots(&data)[..2], [Some("api-new"), Some("gone")]);
+        assert!(data.is_auto_assigned(0) && !data.is_auto_assigned(1));
+    }
+
+    #[test]
+    fn fills_by_frecency() {
+        let config = AutoAssignConfig {
+            slot_patterns: vec![None; 5],
+            auto_fill: AutoFill::Frecency,
+        };
+        let now = 1_760_000_000;
+        let mut data = HotbarData::default();
+        // Used often but weeks ago, less often within the hour, and in between
+        accessed(&mut data, "old", now - 30 * 86_400, 20);
+        accessed(&mut data, "recent", now - 60, 3);
+        accessed(&mut data, "daily", now - 7_200, 4);
+        data.set_session_at_slot(0, Some("pinned".to_string()));
+
+        let running = sessions(&["daily", "never", "old", "pinned", "recent"]);
+        assert!(config.apply(&mut data, &running, &[], now));
+        // Sessions without any recorded use aren't ranked
+        assert_eq!(
+            slots(&data),
+            [
+                Some("pinned"),
+                Some("recent"),
+                Some("daily"),
+                Some("old"),
+                None
+            ]
+        );
+    }
+    #[test]
+    fn sessions_followed_by_a_main_worktree_slot_count_as_assigned() {
+        let config = AutoAssignConfig {
+            slot_patterns: vec![None, Some(Regex::new("^api").unwrap()), None, None, None],
+            auto_fill: AutoFill::Off,
+        };
+        let projects = vec![Project {
+            name: "api@main".to_string(),
+            path: "/src/api".into(),
+            repo: "api".to_string(),
+            branch: Some("main".to_string()),
+            is_main_worktree: true,
+        }];
+        let mut data = HotbarData::default();
+        data.set_session_at_slot(0, Some("api/".to_string()));
+
+        let changed = config.apply(
+            &mut data,
+            &sessions(&["api@main", "api@login"]),
+            &projects,
+            0,
+        );
+        assert!(changed);
+        assert_eq!(
+            data.get_session_at_slot(1).map(String::as_str),
+            Some("api@login")
+        );
+    }
+
+    #[test]
+    fn leaves_slots_the_user_cleared_empty() {
+        let config = AutoAssignConfig {
+            slot_patterns: vec![Some(Regex::new("^api").unwrap()), None, None, None, None],
+            auto_fill: AutoFill::Off,
+        };
+        let mut data = HotbarData::default();
+        assert!(config.apply(&mut data, &sessions(&["api"]), &[], 0));
+
+        data.edit_slots(&[(0, None)]);
+        assert!(!config.apply(&mut data, &sessions(&["api"]), &[], 0));
+        assert_eq!(data.get_session_at_slot(0), None);
+
+        // Until the user assigns the slot again
+        data.edit_slots(&[(0, Some("docs".to_string()))]);
+        assert!(!data.is_cleared(0));
+    }
+}
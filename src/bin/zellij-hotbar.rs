@@ -0,0 +1,371 @@
+//! Manages the hotbar data file from the shell, e.g. to bootstrap slots from
+//! dotfiles. The plugin picks changes up the next time it reloads the file.
+
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use std::error::Error;
+use std::path::PathBuf;
+use zellij_hotbar_manager::dates;
+use zellij_hotbar_manager::error::HotbarError;
+use zellij_hotbar_manager::persistence::{self, DataFile, HotbarData};
+use zellij_utils::consts::ZELLIJ_TMP_DIR;
+
+#[derive(Parser)]
+#[clap(
+    name = "zellij-hotbar",
+    version,
+    about = "Manage the zellij hotbar from the shell"
+)]
+struct Cli {
+    /// Data file to use instead of the plugin's, which is in
+    /// $TMPDIR/zellij-<uid>/, the host directory behind the plugin's /tmp
+    #[clap(long, global = true, value_parser)]
+    file: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Show what each slot holds
+    List,
+    /// Put a session in a slot (1-5)
+    Set {
+        #[clap(value_parser = clap::value_parser!(u8).range(1..=5))]
+        slot: u8,
+        session: String,
+        /// Replace the session of a locked slot
+        #[clap(long)]
+        force: bool,
+    },
+    /// Empty one slot, or every slot when none is given
+    Clear {
+        #[clap(value_parser = clap::value_parser!(u8).range(1..=5))]
+        slot: Option<u8>,
+        /// Also empty locked slots
+        #[clap(long)]
+        force: bool,
+    },
+    /// Write the data as JSON to a file, or to stdout
+    Export {
+        #[clap(value_parser)]
+        output: Option<PathBuf>,
+    },
+    /// Replace all of the data with a JSON export: slots, locks, notes, tags,
+    /// undo history and focus time
+    Import {
+        #[clap(value_parser)]
+        input: PathBuf,
+        /// Also replace the sessions of locked slots
+        #[clap(long)]
+        force: bool,
+    },
+    /// Check the data file and its backups for problems
+    Doctor,
+    /// Print a completion script for a shell
+    Completions {
+        #[clap(value_parser)]
+        shell: Shell,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let data_file = DataFile::new(
+        cli.file
+            .unwrap_or_else(|| ZELLIJ_TMP_DIR.join(persistence::DATA_FILE_NAME)),
+    );
+
+    let result = match cli.command {
+        Command::List => list(&data_file),
+        Command::Set {
+            slot,
+            session,
+            force,
+        } => edit(&data_file, &[(slot as usize - 1, Some(session))], force),
+        Command::Clear {
+            slot: Some(slot),
+            force,
+        } => edit(&data_file, &[(slot as usize - 1, None)], force),
+        Command::Clear { slot: None, force } => {
+            let edits: Vec<(usize, Option<String>)> = (0..5).map(|slot| (slot, None)).collect();
+            edit(&data_file, &edits, force)
+        }
+        Command::Export { output } => export(&data_file, output),
+        Command::Import { input, force } => import(&data_file, input, force),
+        Command::Doctor => doctor(&data_file),
+        Command::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "zellij-hotbar",
+                &mut std::io::stdout(),
+            );
+            Ok(())
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("zellij-hotbar: {}", e);
+        std::process::exit(1);
+    }
+}
+
+// A missing file is an empty hotbar, as it is for the plugin
+fn load(data_file: &DataFile) -> Result<HotbarData, Box<dyn Error>> {
+    if !data_file.path().exists() {
+        return Ok(HotbarData::default());
+    }
+    let data = data_file.load()?;
+    let problems = data.problems();
+    if !problems.is_empty() {
+        return Err(format!(
+            "{} is inconsistent ({}), see `zellij-hotbar doctor`",
+            data_file.path().display(),
+            problems.join(", ")
+        )
+        .into());
+    }
+    Ok(data)
+}
+
+fn list(data_file: &DataFile) -> Result<(), Box<dyn Error>> {
+    let data = load(data_file)?;
+    for slot in 0..5 {
+        let mut flags = Vec::new();
+        if data.is_locked(slot) {
+            flags.push("locked");
+        }
+        if data.is_auto_assigned(slot) {
+            flags.push("auto");
+        }
+        let session = data.get_session_at_slot(slot).map_or("-", String::as_str);
+        if flags.is_empty() {
+            println!("{}  {}", slot + 1, session);
+        } else {
+            println!("{}  {}  ({})", slot + 1, session, flags.join(", "));
+        }
+    }
+    Ok(())
+}
+
+// Goes through the undo journal like edits in the plugin, so `u` there reverts it
+fn edit(
+    data_file: &DataFile,
+    edits: &[(usize, Option<String>)],
+    force: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut data = load(data_file)?;
+
+    let locked = if force {
+        Vec::new()
+    } else {
+        data.locked_changes(edits)
+    };
+    if !locked.is_empty() && edits.len() == 1 {
+        return Err(format!("slot {} is locked, use --force to change it", locked[0] + 1).into());
+    }
+    for slot in &locked {
+        eprintln!("Skipping locked slot {}", slot + 1);
+    }
+    let allowed: Vec<(usize, Option<String>)> = edits
+        .iter()
+        .filter(|(slot, _)| !locked.contains(slot))
+        .cloned()
+        .collect();
+
+    if data.edit_slots(&allowed) {
+        data_file.save(&data)?;
+    }
+    Ok(())
+}
+
+fn export(data_file: &DataFile, output: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let data = load(data_file)?;
+    let json = serde_json::to_string_pretty(&data)?;
+    match output {
+        Some(path) => std::fs::write(path, json + "\n")?,
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+fn import(data_file: &DataFile, input: PathBuf, force: bool) -> Result<(), Box<dyn Error>> {
+    let json = std::fs::read_to_string(&input)?;
+    let data: HotbarData = serde_json::from_str(&json)
+        .map_err(|e| format!("{} is not a hotbar export: {}", input.display(), e))?;
+    let problems = data.problems();
+    if !problems.is_empty() {
+        return Err(format!(
+            "{} is inconsistent: {}",
+            input.display(),
+            problems.join(", ")
+        )
+        .into());
+    }
+    // An unreadable data file has nothing left to protect, saving moves it aside
+    if !force && let Ok(current) = data_file.load() {
+        let edits: Vec<(usize, Option<String>)> = (0..5)
+            .map(|slot| (slot, data.get_session_at_slot(slot).cloned()))
+            .collect();
+        let locked = current.locked_changes(&edits);
+        if !locked.is_empty() {
+            let slots: Vec<String> = locked.iter().map(|slot| (slot + 1).to_string()).collect();
+            return Err(format!(
+                "the import replaces locked slots: {}, use --force to import anyway",
+                slots.join(", ")
+            )
+            .into());
+        }
+    }
+    // Saving snapshots the replaced slots, so `restore_backup` can bring them back
+    data_file.save(&data)?;
+    Ok(())
+}
+
+fn doctor(data_file: &DataFile) -> Result<(), Box<dyn Error>> {
+    let path = data_file.path();
+    println!("Data file: {}", path.display());
+
+    let mut healthy = true;
+    if !path.exists() {
+        println!("  not created yet, the plugin starts with an empty hotbar");
+    } else {
+        match data_file.load() {
+            Ok(data) => {
+                let problems = data.problems();
+                if problems.is_empty() {
+                    println!("  ok");
+                }
+                for problem in problems {
+                    println!("  problem: {}", problem);
+                    healthy = false;
+                }
+            }
+            Err(e @ HotbarError::VersionMismatch { .. }) => {
+                println!("  unreadable: {}", e);
+                println!("  use the zellij-hotbar that comes with the plugin version in use");
+                healthy = false;
+            }
+            Err(e) => {
+                println!("  unreadable: {}", e);
+                println!("  restore a backup with `b` in the plugin, or import an export");
+                healthy = false;
+            }
+        }
+    }
+
+    let mut tmp_path = path.to_path_buf().into_os_string();
+    tmp_path.push(".tmp");
+    if PathBuf::from(&tmp_path).exists() {
+        println!(
+            "  leftover {} from an interrupted save, safe to delete",
+            PathBuf::from(&tmp_path).display()
+        );
+    }
+
+    let backups = data_file.list_backups();
+    match backups.first() {
+        Some(newest) => println!(
+            "Backups: {}, newest from {}",
+            backups.len(),
+            dates::format_timestamp(newest.timestamp)
+        ),
+        None => println!("Backups: none"),
+    }
+    for (path, e) in data_file.unreadable_backups() {
+        println!("  unreadable backup {}: {}", path.display(), e);
+    }
+
+    if healthy {
+        Ok(())
+    } else {
+        Err("the data file needs attention".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Slots 1 and 2 hold api and docs, slot 1 locked
+    fn locked_hotbar(name: &str) -> DataFile {
+        let data_file = DataFile::temp(name);
+        let mut data = HotbarData::default();
+        data.edit_slots(&[(0, Some("api".to_string())), (1, Some("docs".to_string()))]);
+        data.toggle_lock(0);
+        data_file.save(&data).unwrap();
+        data_file
+    }
+
+    fn slots(data_file: &DataFile) -> Vec<Option<String>> {
+        data_file.load().unwrap().favorites
+    }
+
+    #[test]
+    fn set_leaves_locked_slots_alone_without_force() {
+        let data_file = locked_hotbar("set_locked");
+        let before = std::fs::read(data_file.path()).unwrap();
+
+        let error = edit(&data_file, &[(0, Some("web".to_string()))], false).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "slot 1 is locked, use --force to change it"
+        );
+        assert_eq!(std::fs::read(data_file.path()).unwrap(), before);
+
+        edit(&data_file, &[(0, Some("web".to_string()))], true).unwrap();
+        assert_eq!(slots(&data_file)[0].as_deref(), Some("web"));
+    }
+
+    #[test]
+    fn clearing_every_slot_skips_locked_ones() {
+        let data_file = locked_hotbar("clear_all");
+        let edits: Vec<(usize, Option<String>)> = (0..5).map(|slot| (slot, None)).collect();
+
+        edit(&data_file, &edits, false).unwrap();
+        assert_eq!(
+            slots(&data_file),
+            vec![Some("api".to_string()), None, None, None, None]
+        );
+    }
+
+    #[test]
+    fn exports_import_losslessly() {
+        let data_file = locked_hotbar("round_trip");
+        let mut data = data_file.load().unwrap();
+        data.set_note("api", "the backend, \"v2\"");
+        data.set_tags("api", "work, rust");
+        data.add_focus_time("api", 1_760_000_000, 1_760_003_600);
+        data.set_auto_session_at_slot(2, Some("web".to_string()));
+        data_file.save(&data).unwrap();
+
+        let export_path = data_file.path().with_file_name("hotbar.json");
+        export(&data_file, Some(export_path.clone())).unwrap();
+        let imported = DataFile::temp("round_trip_import");
+        import(&imported, export_path, false).unwrap();
+
+        let json = |data_file: &DataFile| serde_json::to_value(data_file.load().unwrap()).unwrap();
+        assert_eq!(json(&imported), json(&data_file));
+    }
+
+    #[test]
+    fn import_leaves_locked_slots_alone_without_force() {
+        let data_file = locked_hotbar("import_locked");
+        let mut export = HotbarData::default();
+        export.set_session_at_slot(0, Some("web".to_string()));
+        let export_path = data_file.path().with_file_name("hotbar.json");
+        std::fs::write(&export_path, serde_json::to_string(&export).unwrap()).unwrap();
+
+        let error = import(&data_file, export_path.clone(), false).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the import replaces locked slots: 1, use --force to import anyway"
+        );
+        assert_eq!(slots(&data_file)[0].as_deref(), Some("api"));
+
+        import(&data_file, export_path, true).unwrap();
+        assert_eq!(slots(&data_file)[0].as_deref(), Some("web"));
+    }
+}
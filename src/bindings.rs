@@ -0,0 +1,304 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    NEXT_SLOT, OPEN_RECENT, PREVIOUS_SLOT, REDO, SWITCH_SLOT_PREFIX, SWITCHER, TOGGLE_UI, UNDO,
+};
+
+/// The alias the README registers the plugin under.
+pub const DEFAULT_PLUGIN_ALIAS: &str = "hotbar-manager";
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum BindingFormat {
+    /// A `keybinds` block for the zellij config
+    #[default]
+    Kdl,
+    /// Keymaps for Neovim running inside zellij
+    Neovim,
+    /// `bind-key` lines for tmux-style configs
+    Tmux,
+    /// Shell aliases
+    Shell,
+}
+
+impl BindingFormat {
+    pub const ALL: [BindingFormat; 4] = [
+        BindingFormat::Kdl,
+        BindingFormat::Neovim,
+        BindingFormat::Tmux,
+        BindingFormat::Shell,
+    ];
+
+    pub fn from_arg(format: &str) -> Option<Self> {
+        match format {
+            "kdl" => Some(BindingFormat::Kdl),
+            "nvim" | "neovim" | "lua" => Some(BindingFormat::Neovim),
+            "tmux" => Some(BindingFormat::Tmux),
+            "shell" | "sh" => Some(BindingFormat::Shell),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BindingFormat::Kdl => "Zellij (KDL)",
+            BindingFormat::Neovim => "Neovim (Lua)",
+            BindingFormat::Tmux => "tmux",
+            BindingFormat::Shell => "Shell",
+        }
+    }
+
+    fn comment(&self) -> &'static str {
+        match self {
+            BindingFormat::Kdl => "//",
+            BindingFormat::Neovim => "--",
+            BindingFormat::Tmux | BindingFormat::Shell => "#",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|f| *f == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|f| *f == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// Where a binding goes in the zellij config: available everywhere, or only
+/// after entering session mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scope {
+    Global,
+    SessionMode,
+}
+
+/// A pipe command and the key suggested for it. `key` is the modifier-free
+/// key: digits are bound with Ctrl, letters in session mode.
+struct Binding {
+    command: String,
+    key: char,
+    scope: Scope,
+    description: String,
+}
+
+fn bindings() -> Vec<Binding> {
+    let mut bindings: Vec<Binding> = (1..=5)
+        .map(|slot| Binding {
+            command: format!("{}{}", SWITCH_SLOT_PREFIX, slot),
+            key: char::from_digit(slot, 10).unwrap(),
+            scope: Scope::Global,
+            description: format!("Switch to hotbar slot {}", slot),
+        })
+        .collect();
+    bindings.push(Binding {
+        command: OPEN_RECENT.to_string(),
+        key: '0',
+        scope: Scope::Global,
+        description: "Switch to the previous session".to_string(),
+    });
+    bindings.push(Binding {
+        command: SWITCHER.to_string(),
+        key: '9',
+        scope: Scope::Global,
+        description: "Cycle through recent sessions, switching after a pause".to_string(),
+    });
+    for (command, key, description) in [
+        (UNDO, 'u', "Undo the last hotbar edit"),
+        (REDO, 'r', "Redo the last undone hotbar edit"),
+        (TOGGLE_UI, 'h', "Toggle the hotbar manager"),
+        (NEXT_SLOT, ']', "Switch to the next running hotbar session"),
+        (
+            PREVIOUS_SLOT,
+            '[',
+            "Switch to the previous running hotbar session",
+        ),
+    ] {
+        bindings.push(Binding {
+            command: command.to_string(),
+            key,
+            scope: Scope::SessionMode,
+            description: description.to_string(),
+        });
+    }
+    bindings
+}
+
+/// What a `generate_bindings` pipe asks for, read from its args, e.g.
+/// `--args "format=nvim,plugin=hotbar"`. Without a format every snippet is
+/// generated.
+pub struct BindingOptions {
+    pub formats: Vec<BindingFormat>,
+    pub plugin_alias: String,
+}
+
+impl BindingOptions {
+    pub fn from_args(args: &BTreeMap<String, String>) -> Result<Self, String> {
+        let formats = match args.get("format").map(String::as_str) {
+            None | Some("all") => BindingFormat::ALL.to_vec(),
+            Some(format) => vec![
+                BindingFormat::from_arg(format)
+                    .ok_or_else(|| format!("Unknown bindings format: {}", format))?,
+            ],
+        };
+        let plugin_alias = args
+            .get("plugin")
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_PLUGIN_ALIAS.to_string());
+
+        Ok(Self {
+            formats,
+            plugin_alias,
+        })
+    }
+}
+
+pub fn generate_all(options: &BindingOptions) -> String {
+    options
+        .formats
+        .iter()
+        .map(|format| {
+            format!(
+                "{} {}\n{}",
+                format.comment(),
+                format.name(),
+                generate(*format, &options.plugin_alias)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn generate(format: BindingFormat, plugin_alias: &str) -> String {
+    let bindings = bindings();
+    match format {
+        BindingFormat::Kdl => kdl(&bindings, plugin_alias),
+        BindingFormat::Neovim => neovim(&bindings),
+        BindingFormat::Tmux => tmux(&bindings),
+        BindingFormat::Shell => shell(&bindings),
+    }
+}
+
+fn pipe_command(command: &str) -> String {
+    format!("zellij action pipe --name \"{}\"", command)
+}
+
+fn kdl(bindings: &[Binding], plugin_alias: &str) -> String {
+    let bind_block = |binding: &Binding, key: String| {
+        format!(
+            "        // {}\n        bind \"{}\" {{\n            MessagePlugin \"{}\" {{ name \"{}\"; }}\n            SwitchToMode \"normal\";\n        }}\n",
+            binding.description, key, plugin_alias, binding.command
+        )
+    };
+
+    let mut out = String::from("keybinds {\n    shared_except \"locked\" \"move\" {\n");
+    for binding in bindings.iter().filter(|b| b.scope == Scope::Global) {
+        out.push_str(&bind_block(binding, format!("Ctrl {}", binding.key)));
+    }
+    out.push_str("    }\n\n    session {\n");
+    for binding in bindings.iter().filter(|b| b.scope == Scope::SessionMode) {
+        out.push_str(&bind_block(binding, binding.key.to_string()));
+    }
+    out.push_str("    }\n}\n");
+    out
+}
+
+// Only the Ctrl bindings: inside Neovim they would otherwise never reach zellij
+fn neovim(bindings: &[Binding]) -> String {
+    let mut out = String::from("if os.getenv('ZELLIJ') then\n");
+    for binding in bindings.iter().filter(|b| b.scope == Scope::Global) {
+        out.push_str(&format!(
+            "  vim.keymap.set({{ 'n', 'i', 't', 'v' }}, '<C-{}>', function()\n    vim.fn.system('{}')\n  end, {{ silent = true, desc = '{}' }})\n",
+            binding.key,
+            pipe_command(&binding.command),
+            binding.description
+        ));
+    }
+    out.push_str("end\n");
+    out
+}
+
+fn tmux(bindings: &[Binding]) -> String {
+    let mut out = String::new();
+    for binding in bindings {
+        let key = match binding.scope {
+            Scope::Global => format!("C-{}", binding.key),
+            Scope::SessionMode => binding.key.to_string(),
+        };
+        out.push_str(&format!(
+            "# {}\nbind-key {} run-shell '{}'\n",
+            binding.description,
+            key,
+            pipe_command(&binding.command)
+        ));
+    }
+    out
+}
+
+fn shell(bindings: &[Binding]) -> String {
+    let mut out = String::new();
+    for binding in bindings {
+        let alias = match binding.command.strip_prefix("hotbar_") {
+            Some(command) => format!("hotbar-{}", command),
+            None => format!("hotbar-{}", binding.command),
+        }
+        .replace('_', "-");
+        out.push_str(&format!(
+            "alias {}='{}'  # {}\n",
+            alias,
+            pipe_command(&binding.command),
+            binding.description
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The README shows generated snippets, so they can't drift from the plugin
+    #[test]
+    fn readme_shows_generated_snippets() {
+        let readme = include_str!("../README.md");
+        for format in [BindingFormat::Kdl, BindingFormat::Neovim] {
+            let snippet = generate(format, DEFAULT_PLUGIN_ALIAS);
+            assert!(
+                readme.contains(&snippet),
+                "README is missing the generated {} snippet:\n{}",
+                format.name(),
+                snippet
+            );
+        }
+    }
+
+    #[test]
+    fn binds_every_slot() {
+        let kdl = generate(BindingFormat::Kdl, "hotbar");
+        for slot in 1..=5 {
+            assert!(kdl.contains(&format!(
+                "bind \"Ctrl {}\" {{\n            MessagePlugin \"hotbar\" {{ name \"switch_slot_{}\"; }}",
+                slot, slot
+            )));
+        }
+    }
+
+    #[test]
+    fn reads_options_from_args() {
+        let args = [
+            ("format".to_string(), "tmux".to_string()),
+            ("plugin".to_string(), "hotbar".to_string()),
+        ]
+        .into();
+        let options = BindingOptions::from_args(&args).unwrap();
+        assert_eq!(options.formats, vec![BindingFormat::Tmux]);
+        assert_eq!(options.plugin_alias, "hotbar");
+
+        let options = BindingOptions::from_args(&BTreeMap::new()).unwrap();
+        assert_eq!(options.formats, BindingFormat::ALL.to_vec());
+
+        let args = [("format".to_string(), "emacs".to_string())].into();
+        assert!(BindingOptions::from_args(&args).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+pub const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Formats a unix timestamp as `YYYY-MM-DD HH:MM:SS` (UTC).
+pub fn format_timestamp(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days(timestamp / SECONDS_PER_DAY);
+    let seconds = timestamp % SECONDS_PER_DAY;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
+/// Formats days since the unix epoch as `YYYY-MM-DD`.
+pub fn format_day(day: u64) -> String {
+    let (year, month, day) = civil_from_days(day);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Parses a `YYYY-MM-DD` date into days since the unix epoch.
+pub fn parse_day(date: &str) -> Option<u64> {
+    let mut parts = date.trim().splitn(3, '-');
+    let year = parts.next()?.parse::<u64>().ok()?;
+    let month = parts.next()?.parse::<u64>().ok()?;
+    let day = parts.next()?.parse::<u64>().ok()?;
+    if year < 1970 || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+pub fn day_of(timestamp: u64) -> u64 {
+    timestamp / SECONDS_PER_DAY
+}
+
+// Converts days since the unix epoch to a (year, month, day) date, using
+// Howard Hinnant's `civil_from_days` algorithm
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+// The inverse of `civil_from_days`
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let yoe = year - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Formats a duration compactly, e.g. `2h 05m` or `45s`.
+pub fn format_duration(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3_600 => format!("{}m", seconds / 60),
+        _ => format!("{}h {:02}m", seconds / 3_600, seconds % 3_600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_dates_around_leap_years_and_month_ends() {
+        for date in [
+            "1970-01-01",
+            "1999-12-31",
+            "2000-02-28",
+            "2000-02-29",
+            "2000-03-01",
+            "2023-02-28",
+            "2023-03-01",
+            "2024-02-29",
+            "2024-04-30",
+            "2024-12-31",
+            "2100-02-28",
+            "2100-03-01",
+        ] {
+            let day = parse_day(date).unwrap();
+            assert_eq!(format_day(day), date);
+        }
+
+        assert_eq!(parse_day("1970-01-01"), Some(0));
+        assert_eq!(
+            parse_day("2024-03-01").unwrap() - parse_day("2024-02-28").unwrap(),
+            2
+        );
+        // Not a leap year, as it's divisible by 100 but not by 400
+        assert_eq!(
+            parse_day("2100-03-01").unwrap() - parse_day("2100-02-28").unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn converts_every_day_back_and_forth() {
+        for day in 0..(200 * 366) {
+            let (year, month, day_of_month) = civil_from_days(day);
+            assert_eq!(days_from_civil(year, month, day_of_month), day);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for date in [
+            "",
+            "2024",
+            "2024-02",
+            "1969-12-31",
+            "2024-00-10",
+            "2024-13-01",
+            "2024-01-32",
+            "2024-1x-01",
+        ] {
+            assert_eq!(parse_day(date), None, "{}", date);
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::host::Host;
+
+pub const PROJECT_ROOTS_KEY: &str = "project_roots";
+pub const PROJECT_SCAN_DEPTH_KEY: &str = "project_scan_depth";
+pub const SCAN_CONTEXT_KEY: &str = "hotbar_scan";
+
+const DEFAULT_SCAN_DEPTH: usize = 3;
+
+// Expands a leading `~` with the host's $HOME and lists every repository below
+// each root without descending into the repositories themselves. Each
+// repository prints a `repo` line for its main checkout followed by one
+// `worktree` line per linked worktree registered in `.git/worktrees`. Linked
+// worktrees found directly under a root (`.git` is a file) are skipped, as
+// their repository already reports them.
+const SCAN_SCRIPT: &str = r#"depth="$1"; shift
+branch() { sed -n 's|^ref: refs/heads/||p' "$1/HEAD" 2>/dev/null; }
+for root in "$@"; do
+    case "$root" in "~"*) root="$HOME${root#"~"}";; esac
+    find "$root" -mindepth 2 -maxdepth "$depth" -name .git -prune -print 2>/dev/null |
+    while IFS= read -r git; do
+        [ -d "$git" ] || continue
+        repo="${git%/.git}"
+        printf 'repo\t%s\t%s\n' "$repo" "$(branch "$git")"
+        for worktree in "$git"/worktrees/*; do
+            [ -f "$worktree/gitdir" ] || continue
+            checkout="$(dirname "$(cat "$worktree/gitdir")")"
+            printf 'worktree\t%s\t%s\t%s\n' "$repo" "$checkout" "$(branch "$worktree")"
+        done
+    done
+done"#;
+
+/// Separates the repository from the branch in worktree session names.
+pub const WORKTREE_SEPARATOR: char = '@';
+/// Ends slot references that follow a repository's main worktree. Zellij
+/// session names can't contain slashes, so no session is ever mistaken for one.
+pub const MAIN_WORKTREE_MARKER: char = '/';
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Project {
+    pub name: String,
+    pub path: PathBuf,
+    /// Name of the repository this checkout belongs to
+    pub repo: String,
+    pub branch: Option<String>,
+    pub is_main_worktree: bool,
+}
+
+impl Project {
+    /// The slot reference that follows this repository's main worktree,
+    /// whichever branch it has checked out (e.g. `api/`).
+    pub fn main_worktree_ref(&self) -> String {
+        format!("{}{}", self.repo, MAIN_WORKTREE_MARKER)
+    }
+}
+
+/// Returns the repository name when `reference` points at a repository's
+/// main worktree rather than a specific session.
+pub fn main_worktree_repo(reference: &str) -> Option<&str> {
+    reference
+        .strip_suffix(MAIN_WORKTREE_MARKER)
+        .filter(|repo| !repo.is_empty())
+}
+
+/// The checkout a `repo/` slot reference currently follows, if discovery
+/// has found the repository.
+pub fn main_worktree_of<'a>(projects: &'a [Project], repo: &str) -> Option<&'a Project> {
+    projects
+        .iter()
+        .find(|project| project.repo == repo && project.is_main_worktree)
+}
+
+#[derive(Debug, Clone)]
+pub struct DiscoveryConfig {
+    pub roots: Vec<String>,
+    pub depth: usize,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            roots: Vec::new(),
+            depth: DEFAULT_SCAN_DEPTH,
+        }
+    }
+}
+
+impl DiscoveryConfig {
+    pub fn from_configuration(configuration: &BTreeMap<String, String>) -> Self {
+        let roots = configuration
+            .get(PROJECT_ROOTS_KEY)
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|root| !root.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let depth = configuration
+            .get(PROJECT_SCAN_DEPTH_KEY)
+            .and_then(|value| value.parse::<usize>().ok())
+            .filter(|depth| *depth >= 2)
+            .unwrap_or(DEFAULT_SCAN_DEPTH);
+
+        Self { roots, depth }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.roots.is_empty()
+    }
+}
+
+/// Starts a background scan of the configured roots. Results arrive as a
+/// `RunCommandResult` event carrying `SCAN_CONTEXT_KEY` in its context.
+pub fn start_scan(host: &dyn Host, config: &DiscoveryConfig) {
+    if !config.is_enabled() {
+        return;
+    }
+
+    let depth = config.depth.to_string();
+    let mut command = vec!["sh", "-c", SCAN_SCRIPT, "sh", depth.as_str()];
+    command.extend(config.roots.iter().map(String::as_str));
+
+    let mut context = BTreeMap::new();
+    context.insert(SCAN_CONTEXT_KEY.to_string(), String::new());
+    host.run_command(&command, context);
+}
+
+pub fn is_scan_result(context: &BTreeMap<String, String>) -> bool {
+    context.contains_key(SCAN_CONTEXT_KEY)
+}
+
+struct Checkout {
+    repo_path: PathBuf,
+    path: PathBuf,
+    branch: Option<String>,
+    is_main_worktree: bool,
+}
+
+/// Turns the scan output into projects. Repositories without linked
+/// worktrees are named after their directory; every checkout of a repository
+/// with worktrees is named `repo@branch`. When two roots contain a repository
+/// with the same name, the first one found wins.
+pub fn parse_scan_output(stdout: &[u8]) -> Vec<Project> {
+    let checkouts: Vec<Checkout> = String::from_utf8_lossy(stdout)
+        .lines()
+        .filter_map(parse_scan_line)
+        .collect();
+
+    let mut projects: Vec<Project> = Vec::new();
+    let mut repo_paths: Vec<(&str, &PathBuf)> = Vec::new();
+    for checkout in &checkouts {
+        let Some(repo) = checkout
+            .repo_path
+            .file_name()
+            .and_then(|name| name.to_str())
+        else {
+            continue;
+        };
+
+        match repo_paths.iter().find(|(name, _)| *name == repo) {
+            Some((_, path)) if *path != &checkout.repo_path => continue,
+            Some(_) => {}
+            None => repo_paths.push((repo, &checkout.repo_path)),
+        }
+
+        let has_worktrees = checkouts
+            .iter()
+            .any(|other| other.repo_path == checkout.repo_path && !other.is_main_worktree);
+        let name = if has_worktrees {
+            format!("{}{}{}", repo, WORKTREE_SEPARATOR, checkout_label(checkout))
+        } else {
+            repo.to_string()
+        };
+
+        if projects.iter().any(|project| project.name == name) {
+            continue;
+        }
+
+        projects.push(Project {
+            name,
+            path: checkout.path.clone(),
+            repo: repo.to_string(),
+            branch: checkout.branch.clone(),
+            is_main_worktree: checkout.is_main_worktree,
+        });
+    }
+
+    projects.sort_by(|a, b| a.name.cmp(&b.name));
+    projects
+}
+
+fn parse_scan_line(line: &str) -> Option<Checkout> {
+    let mut fields = line.split('\t');
+    let kind = fields.next()?;
+    let repo_path = PathBuf::from(fields.next()?);
+
+    let (path, is_main_worktree) = match kind {
+        "repo" => (repo_path.clone(), true),
+        "worktree" => (PathBuf::from(fields.next()?), false),
+        _ => return None,
+    };
+
+    let branch = fields
+        .next()
+        .map(str::trim)
+        .filter(|branch| !branch.is_empty())
+        .map(String::from);
+
+    Some(Checkout {
+        repo_path,
+        path,
+        branch,
+        is_main_worktree,
+    })
+}
+
+// `feature/login` becomes `feature-login`, see `MAIN_WORKTREE_MARKER`.
+// Detached checkouts fall back to their directory name.
+fn checkout_label(checkout: &Checkout) -> String {
+    match &checkout.branch {
+        Some(branch) => branch.replace('/', "-"),
+        None => checkout
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(projects: &[Project]) -> Vec<&str> {
+        projects
+            .iter()
+            .map(|project| project.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn the_first_root_wins_for_repositories_of_the_same_name() {
+        let output = "repo\t/work/api\tmain\nrepo\t/home/me/api\tdev\n";
+        let projects = parse_scan_output(output.as_bytes());
+
+        assert_eq!(names(&projects), ["api"]);
+        assert_eq!(projects[0].path, PathBuf::from("/work/api"));
+    }
+
+    #[test]
+    fn skips_malformed_and_unknown_lines() {
+        let output =
+            "repo\nworktree\t/src/api\nsubmodule\t/src/lib\tmain\n\nrepo\t/src/docs\tmain\n";
+        let projects = parse_scan_output(output.as_bytes());
+
+        assert_eq!(names(&projects), ["docs"]);
+    }
+
+    #[test]
+    fn sorts_projects_by_name() {
+        let output = "repo\t/src/web\tmain\nrepo\t/src/api\tmain\nrepo\t/src/docs\t\n";
+        let projects = parse_scan_output(output.as_bytes());
+
+        assert_eq!(names(&projects), ["api", "docs", "web"]);
+        assert_eq!(projects[1].branch, None);
+    }
+
+    #[test]
+    fn names_checkouts_of_repositories_with_worktrees_after_their_branch() {
+        let output = "repo\t/src/api\tmain\n\
+                      worktree\t/src/api\t/src/api-login\tfeature/login\n\
+                      worktree\t/src/api\t/src/api-bisect\t\n\
+                      repo\t/src/docs\tmain\n";
+        let projects = parse_scan_output(output.as_bytes());
+
+        // Detached checkouts are named after their directory
+        assert_eq!(
+            names(&projects),
+            ["api@api-bisect", "api@feature-login", "api@main", "docs"]
+        );
+        let main = projects
+            .iter()
+            .find(|project| project.is_main_worktree)
+            .unwrap();
+        assert_eq!(main.name, "api@main");
+        assert_eq!(main.main_worktree_ref(), "api/");
+        assert_eq!(projects[1].branch.as_deref(), Some("feature/login"));
+    }
+
+    #[test]
+    fn recognizes_main_worktree_references() {
+        assert_eq!(main_worktree_repo("api/"), Some("api"));
+        assert_eq!(main_worktree_repo("/"), None);
+        assert_eq!(main_worktree_repo("api"), None);
+        assert_eq!(main_worktree_repo("api@main"), None);
+    }
+}
@@ -0,0 +1,82 @@
+use std::fmt;
+
+/// Where a switch was headed when there turned out to be nothing to switch to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SwitchTarget {
+    /// A hotbar slot, 0-based
+    Slot(usize),
+    /// A `hotbar_*` key from the plugin configuration
+    Key(String),
+    Previous,
+    /// The next or previous occupied slot
+    Cycle,
+    /// The main worktree of a repository, which discovery hasn't reported
+    MainWorktree(String),
+}
+
+#[derive(Debug)]
+pub enum HotbarError {
+    /// Reading or writing the data file failed
+    Io(std::io::Error),
+    /// The data file, or the data about to be saved, isn't a valid archive
+    CorruptArchive(String),
+    /// The data file was written by a version with a different layout
+    VersionMismatch { found: u32, expected: u32 },
+    /// Nothing is saved where a switch was headed
+    MissingSession(SwitchTarget),
+    /// A slot or tab number that doesn't exist, as it was given
+    InvalidSlot(String),
+    /// The user didn't grant a zellij permission the plugin needs
+    PermissionDenied(String),
+    /// No backup at this index, 0 being the newest
+    MissingBackup(usize),
+}
+
+impl fmt::Display for HotbarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotbarError::Io(e) => write!(f, "{}", e),
+            HotbarError::CorruptArchive(e) => write!(f, "not a valid hotbar archive: {}", e),
+            HotbarError::VersionMismatch { found, expected } => write!(
+                f,
+                "written in format version {}, this version reads {}",
+                found, expected
+            ),
+            HotbarError::MissingSession(SwitchTarget::Slot(slot)) => {
+                write!(f, "No session saved in slot {}", slot + 1)
+            }
+            HotbarError::MissingSession(SwitchTarget::Key(key)) => {
+                write!(f, "No session found with key: {}", key)
+            }
+            HotbarError::MissingSession(SwitchTarget::Previous) => {
+                write!(f, "No previous session")
+            }
+            HotbarError::MissingSession(SwitchTarget::Cycle) => {
+                write!(f, "No other running session in the hotbar")
+            }
+            HotbarError::MissingSession(SwitchTarget::MainWorktree(repo)) => {
+                write!(f, "No main worktree found for {} yet", repo)
+            }
+            HotbarError::InvalidSlot(slot) => write!(f, "Invalid slot: {}", slot),
+            HotbarError::PermissionDenied(permission) => {
+                write!(f, "Permission denied: {}", permission)
+            }
+            HotbarError::MissingBackup(index) => write!(f, "No backup at index {}", index),
+        }
+    }
+}
+
+impl std::error::Error for HotbarError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HotbarError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for HotbarError {
+    fn from(e: std::io::Error) -> Self {
+        HotbarError::Io(e)
+    }
+}
@@ -0,0 +1,31 @@
+use crate::persistence::SessionMetadata;
+
+/// Matches a session against a filter query. Every whitespace separated term
+/// has to match: `#term` matches tags starting with `term`, any other term
+/// fuzzy-matches the session name or its note.
+pub fn matches(query: &str, session_name: &str, metadata: Option<&SessionMetadata>) -> bool {
+    query
+        .split_whitespace()
+        .all(|term| match term.strip_prefix('#') {
+            Some(tag) => metadata.is_some_and(|metadata| {
+                metadata
+                    .tags
+                    .iter()
+                    .any(|t| t.to_lowercase().starts_with(&tag.to_lowercase()))
+            }),
+            None => {
+                fuzzy_match(term, session_name)
+                    || metadata.is_some_and(|metadata| fuzzy_match(term, &metadata.note))
+            }
+        })
+}
+
+/// Whether the characters of `pattern` appear in `text` in order,
+/// ignoring case (e.g. `apw` matches `api-web`).
+pub fn fuzzy_match(pattern: &str, text: &str) -> bool {
+    let mut text_chars = text.chars().flat_map(char::to_lowercase);
+    pattern
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|p| text_chars.any(|t| t == p))
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+pub const MODAL_WIDTH_KEY: &str = "modal_width";
+pub const MODAL_HEIGHT_KEY: &str = "modal_height";
+pub const MODAL_ANCHOR_KEY: &str = "modal_anchor";
+// What Zellij gives a floating pane that doesn't ask for a size
+const DEFAULT_SIZE: Size = Size::Percent(50);
+
+/// A width or height, e.g. `80` columns or `60%` of the tab.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Size {
+    Fixed(usize),
+    Percent(usize),
+}
+
+impl Size {
+    pub fn from_config(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let size = match value.strip_suffix('%') {
+            Some(percent) => Size::Percent(percent.trim().parse().ok()?),
+            None => Size::Fixed(value.parse().ok()?),
+        };
+        match size {
+            Size::Fixed(0) | Size::Percent(0) => None,
+            Size::Percent(percent) if percent > 100 => None,
+            size => Some(size),
+        }
+    }
+
+    /// The size in columns or rows out of `full`, never more than that.
+    pub fn resolve(&self, full: usize) -> usize {
+        match self {
+            Size::Fixed(size) => (*size).min(full),
+            Size::Percent(percent) => full * percent / 100,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    #[default]
+    Center,
+    Top,
+    Bottom,
+}
+
+impl Anchor {
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "top" => Anchor::Top,
+            "bottom" => Anchor::Bottom,
+            _ => Anchor::Center,
+        }
+    }
+}
+
+/// Where the modal's floating pane goes, configured with `modal_width`,
+/// `modal_height` and `modal_anchor`. Without any of them Zellij's default
+/// geometry is kept.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ModalGeometry {
+    pub width: Option<Size>,
+    pub height: Option<Size>,
+    pub anchor: Anchor,
+    is_configured: bool,
+}
+
+/// A pane's position and size in the tab, in columns and rows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaneRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl ModalGeometry {
+    pub fn from_configuration(configuration: &BTreeMap<String, String>) -> Self {
+        Self {
+            width: configuration
+                .get(MODAL_WIDTH_KEY)
+                .and_then(|value| Size::from_config(value)),
+            height: configuration
+                .get(MODAL_HEIGHT_KEY)
+                .and_then(|value| Size::from_config(value)),
+            anchor: configuration
+                .get(MODAL_ANCHOR_KEY)
+                .map(|value| Anchor::from_config(value))
+                .unwrap_or_default(),
+            is_configured: [MODAL_WIDTH_KEY, MODAL_HEIGHT_KEY, MODAL_ANCHOR_KEY]
+                .iter()
+                .any(|key| configuration.contains_key(*key)),
+        }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.is_configured
+    }
+
+    /// Whether the modal's box takes the pane's whole height rather than
+    /// shrinking to its content, which would leave a gap inside the pane.
+    pub fn fills_pane_height(&self) -> bool {
+        self.height.is_some() || self.anchor == Anchor::Bottom
+    }
+
+    /// The pane in a tab of `rows` by `cols`, centered horizontally.
+    pub fn pane_rect(&self, rows: usize, cols: usize) -> PaneRect {
+        let width = self.width.unwrap_or(DEFAULT_SIZE).resolve(cols).max(1);
+        let height = self.height.unwrap_or(DEFAULT_SIZE).resolve(rows).max(1);
+        let y = match self.anchor {
+            Anchor::Center => (rows.saturating_sub(height)) / 2,
+            Anchor::Top => 0,
+            Anchor::Bottom => rows.saturating_sub(height),
+        };
+        PaneRect {
+            x: cols.saturating_sub(width) / 2,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geometry(entries: &[(&str, &str)]) -> ModalGeometry {
+        let configuration = entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        ModalGeometry::from_configuration(&configuration)
+    }
+
+    #[test]
+    fn places_the_pane_by_size_and_anchor() {
+        let centered = geometry(&[(MODAL_WIDTH_KEY, "80"), (MODAL_HEIGHT_KEY, "60%")]);
+        assert_eq!(
+            centered.pane_rect(50, 200),
+            PaneRect {
+                x: 60,
+                y: 10,
+                width: 80,
+                height: 30
+            }
+        );
+
+        let bottom = geometry(&[(MODAL_ANCHOR_KEY, "bottom"), (MODAL_WIDTH_KEY, "300")]);
+        assert!(bottom.fills_pane_height());
+        assert_eq!(
+            bottom.pane_rect(50, 200),
+            PaneRect {
+                x: 0,
+                y: 25,
+                width: 200,
+                height: 25
+            }
+        );
+
+        let invalid = geometry(&[(MODAL_WIDTH_KEY, "150%"), (MODAL_HEIGHT_KEY, "tall")]);
+        assert!(invalid.is_configured());
+        assert_eq!((invalid.width, invalid.height), (None, None));
+        assert!(!geometry(&[]).is_configured());
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::persistence::SessionMetadata;
+
+pub const GROUP_BY_KEY: &str = "group_by";
+pub const GROUP_SEPARATOR_KEY: &str = "group_separator";
+// Prefixes are usually set off with a dash (`acme-api`), as session names
+// can't use a slash, see `MAIN_WORKTREE_MARKER`
+pub const DEFAULT_GROUP_SEPARATOR: &str = "-";
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    #[default]
+    Off,
+    /// Group by the part of the name before the separator (`acme-api`)
+    Prefix,
+    /// Group by tag; sessions with several tags appear in each group
+    Tag,
+}
+
+impl GroupBy {
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "prefix" => GroupBy::Prefix,
+            "tag" => GroupBy::Tag,
+            _ => GroupBy::Off,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            GroupBy::Off => GroupBy::Prefix,
+            GroupBy::Prefix => GroupBy::Tag,
+            GroupBy::Tag => GroupBy::Off,
+        }
+    }
+}
+
+/// A row of the session list: either a group header or a session.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListRow {
+    Group {
+        name: String,
+        count: usize,
+        collapsed: bool,
+    },
+    Session {
+        name: String,
+        /// What to display, e.g. `api` for `acme-api` under `acme`
+        label: String,
+        grouped: bool,
+    },
+}
+
+impl ListRow {
+    fn session(name: &str) -> Self {
+        ListRow::Session {
+            name: name.to_string(),
+            label: name.to_string(),
+            grouped: false,
+        }
+    }
+}
+
+/// Builds the list rows for `sessions`. Groups come first, sorted by name,
+/// followed by the sessions that don't belong to any group in their
+/// original order. Collapsed groups only contribute their header.
+pub fn build_rows(
+    sessions: &[String],
+    group_by: GroupBy,
+    separator: &str,
+    metadata: &HashMap<String, SessionMetadata>,
+    collapsed: &HashSet<String>,
+) -> Vec<ListRow> {
+    if group_by == GroupBy::Off {
+        return sessions.iter().map(|name| ListRow::session(name)).collect();
+    }
+
+    let mut groups: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    let mut ungrouped: Vec<ListRow> = Vec::new();
+
+    for name in sessions {
+        let memberships: Vec<(String, String)> = match group_by {
+            GroupBy::Prefix => name
+                .split_once(separator)
+                .filter(|(prefix, rest)| !prefix.is_empty() && !rest.is_empty())
+                .map(|(prefix, rest)| vec![(prefix.to_string(), rest.to_string())])
+                .unwrap_or_default(),
+            GroupBy::Tag => metadata
+                .get(name)
+                .map(|metadata| {
+                    metadata
+                        .tags
+                        .iter()
+                        .map(|tag| (format!("#{}", tag), name.clone()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            GroupBy::Off => Vec::new(),
+        };
+
+        if memberships.is_empty() {
+            ungrouped.push(ListRow::session(name));
+        }
+        for (group, label) in memberships {
+            groups.entry(group).or_default().push((name.clone(), label));
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (group, members) in groups {
+        let is_collapsed = collapsed.contains(&group);
+        rows.push(ListRow::Group {
+            name: group,
+            count: members.len(),
+            collapsed: is_collapsed,
+        });
+        if !is_collapsed {
+            rows.extend(members.into_iter().map(|(name, label)| ListRow::Session {
+                name,
+                label,
+                grouped: true,
+            }));
+        }
+    }
+    rows.extend(ungrouped);
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_by_the_default_separator() {
+        let sessions: Vec<String> = ["acme-api", "acme-web", "dotfiles", "-scratch"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let rows = build_rows(
+            &sessions,
+            GroupBy::Prefix,
+            DEFAULT_GROUP_SEPARATOR,
+            &HashMap::new(),
+            &HashSet::new(),
+        );
+
+        let labels: Vec<String> = rows
+            .iter()
+            .map(|row| match row {
+                ListRow::Group { name, count, .. } => format!("{} ({})", name, count),
+                ListRow::Session { label, .. } => label.clone(),
+            })
+            .collect();
+        assert_eq!(
+            labels,
+            vec!["acme (2)", "api", "web", "dotfiles", "-scratch"]
+        );
+    }
+}
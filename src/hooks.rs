@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+
+use crate::host::Host;
+use crate::logging::Log;
+
+pub const BEFORE_SWITCH_KEY: &str = "before_switch";
+pub const AFTER_SWITCH_KEY: &str = "after_switch";
+pub const BEFORE_SWITCH_TIMEOUT_KEY: &str = "before_switch_timeout_ms";
+// Long enough for a time tracker to stop, short enough that a hook that hangs
+// or never runs doesn't leave the user wondering
+const DEFAULT_BEFORE_SWITCH_TIMEOUT_MS: u64 = 5000;
+
+const HOOK_CONTEXT_KEY: &str = "hotbar_hook";
+const TARGET_CONTEXT_KEY: &str = "target";
+const SLOT_CONTEXT_KEY: &str = "slot";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookStage {
+    /// Runs before leaving the current session; the switch waits for it
+    BeforeSwitch,
+    /// Runs once the switch to the target session has been requested
+    AfterSwitch,
+}
+
+impl HookStage {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HookStage::BeforeSwitch => BEFORE_SWITCH_KEY,
+            HookStage::AfterSwitch => AFTER_SWITCH_KEY,
+        }
+    }
+
+    fn from_str(stage: &str) -> Option<Self> {
+        match stage {
+            BEFORE_SWITCH_KEY => Some(HookStage::BeforeSwitch),
+            AFTER_SWITCH_KEY => Some(HookStage::AfterSwitch),
+            _ => None,
+        }
+    }
+}
+
+/// Shell commands run around switches, configured globally
+/// (`before_switch`, `after_switch`) or per slot (`slot_1_before_switch`, ...).
+/// A slot's own hook replaces the global one, and an empty command disables
+/// the hook, e.g. `slot_3_before_switch ""` for a slot that should skip the
+/// global one.
+pub struct HookConfig {
+    before_switch: Option<String>,
+    after_switch: Option<String>,
+    slot_before_switch: Vec<Option<String>>,
+    slot_after_switch: Vec<Option<String>>,
+    /// How long a switch waits for its before-switch hook
+    pub before_switch_timeout_ms: u64,
+}
+
+impl Default for HookConfig {
+    fn default() -> Self {
+        Self {
+            before_switch: None,
+            after_switch: None,
+            slot_before_switch: Vec::new(),
+            slot_after_switch: Vec::new(),
+            before_switch_timeout_ms: DEFAULT_BEFORE_SWITCH_TIMEOUT_MS,
+        }
+    }
+}
+
+impl HookConfig {
+    pub fn from_configuration(configuration: &BTreeMap<String, String>) -> Self {
+        let slot_hooks = |stage: &str| -> Vec<Option<String>> {
+            (1..=5)
+                .map(|slot| {
+                    configuration
+                        .get(&format!("slot_{}_{}", slot, stage))
+                        .cloned()
+                })
+                .collect()
+        };
+
+        Self {
+            before_switch: configuration.get(BEFORE_SWITCH_KEY).cloned(),
+            after_switch: configuration.get(AFTER_SWITCH_KEY).cloned(),
+            slot_before_switch: slot_hooks(BEFORE_SWITCH_KEY),
+            slot_after_switch: slot_hooks(AFTER_SWITCH_KEY),
+            before_switch_timeout_ms: configuration
+                .get(BEFORE_SWITCH_TIMEOUT_KEY)
+                .and_then(|value| value.parse::<u64>().ok())
+                .filter(|timeout| *timeout > 0)
+                .unwrap_or(DEFAULT_BEFORE_SWITCH_TIMEOUT_MS),
+        }
+    }
+
+    pub fn command_for(&self, stage: HookStage, slot: Option<usize>) -> Option<&str> {
+        let (global, per_slot) = match stage {
+            HookStage::BeforeSwitch => (&self.before_switch, &self.slot_before_switch),
+            HookStage::AfterSwitch => (&self.after_switch, &self.slot_after_switch),
+        };
+
+        slot.and_then(|slot| per_slot.get(slot))
+            .and_then(Option::as_deref)
+            .or(global.as_deref())
+            .filter(|command| !command.trim().is_empty())
+    }
+}
+
+/// A switch waiting for its before-switch hook to report back.
+pub struct WaitingSwitch {
+    pub target: String,
+    pub slot: Option<usize>,
+    /// Unix time in milliseconds when the switch stops waiting
+    pub deadline_ms: u64,
+}
+
+/// A hook's result, as reported back through `RunCommandResult`.
+pub struct HookResult {
+    pub stage: HookStage,
+    pub target: String,
+    pub slot: Option<usize>,
+}
+
+/// Runs `command` with `sh -c` in the background. The hook learns about the
+/// switch from `ZELLIJ_HOTBAR_FROM`, `ZELLIJ_HOTBAR_TO` and
+/// `ZELLIJ_HOTBAR_SLOT` (1-based, empty when not switching from a slot).
+pub fn run_hook(
+    host: &dyn Host,
+    log: &mut Log,
+    stage: HookStage,
+    command: &str,
+    from: Option<&str>,
+    to: &str,
+    slot: Option<usize>,
+) {
+    let slot_str = slot.map(|slot| (slot + 1).to_string()).unwrap_or_default();
+
+    let mut env_variables = BTreeMap::new();
+    env_variables.insert(
+        "ZELLIJ_HOTBAR_FROM".to_string(),
+        from.unwrap_or_default().to_string(),
+    );
+    env_variables.insert("ZELLIJ_HOTBAR_TO".to_string(), to.to_string());
+    env_variables.insert("ZELLIJ_HOTBAR_SLOT".to_string(), slot_str.clone());
+
+    let mut context = BTreeMap::new();
+    context.insert(HOOK_CONTEXT_KEY.to_string(), stage.as_str().to_string());
+    context.insert(TARGET_CONTEXT_KEY.to_string(), to.to_string());
+    context.insert(SLOT_CONTEXT_KEY.to_string(), slot_str);
+
+    log.info(format!(
+        "Running {} hook for '{}': {}",
+        stage.as_str(),
+        to,
+        command
+    ));
+    host.run_command_with_env(&["sh", "-c", command], env_variables, context);
+}
+
+pub fn parse_hook_result(context: &BTreeMap<String, String>) -> Option<HookResult> {
+    let stage = HookStage::from_str(context.get(HOOK_CONTEXT_KEY)?)?;
+    let target = context.get(TARGET_CONTEXT_KEY)?.clone();
+    let slot = context
+        .get(SLOT_CONTEXT_KEY)
+        .and_then(|slot| slot.parse::<usize>().ok())
+        .and_then(|slot| slot.checked_sub(1));
+
+    Some(HookResult {
+        stage,
+        target,
+        slot,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(pairs: &[(&str, &str)]) -> HookConfig {
+        let configuration = pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        HookConfig::from_configuration(&configuration)
+    }
+
+    #[test]
+    fn slot_hooks_replace_the_global_one() {
+        let hooks = config(&[
+            (BEFORE_SWITCH_KEY, "global"),
+            ("slot_2_before_switch", "second"),
+        ]);
+
+        assert_eq!(
+            hooks.command_for(HookStage::BeforeSwitch, Some(1)),
+            Some("second")
+        );
+        assert_eq!(
+            hooks.command_for(HookStage::BeforeSwitch, Some(0)),
+            Some("global")
+        );
+        assert_eq!(
+            hooks.command_for(HookStage::BeforeSwitch, None),
+            Some("global")
+        );
+        assert_eq!(hooks.command_for(HookStage::AfterSwitch, Some(1)), None);
+    }
+
+    #[test]
+    fn empty_commands_disable_hooks() {
+        let hooks = config(&[
+            (BEFORE_SWITCH_KEY, "global"),
+            ("slot_3_before_switch", ""),
+            (AFTER_SWITCH_KEY, " "),
+            ("slot_1_after_switch", "first"),
+        ]);
+
+        assert_eq!(hooks.command_for(HookStage::BeforeSwitch, Some(2)), None);
+        assert_eq!(
+            hooks.command_for(HookStage::BeforeSwitch, Some(3)),
+            Some("global")
+        );
+        assert_eq!(hooks.command_for(HookStage::AfterSwitch, Some(1)), None);
+        assert_eq!(
+            hooks.command_for(HookStage::AfterSwitch, Some(0)),
+            Some("first")
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use zellij_tile::prelude::*;
+
+use crate::geometry::PaneRect;
+
+/// The zellij host functions the plugin calls. Going through this trait
+/// instead of calling `zellij_tile` directly lets the logic run natively in
+/// tests, where the host functions don't exist.
+pub trait Host {
+    fn request_permission(&self, permissions: &[PermissionType]);
+    fn subscribe(&self, event_types: &[EventType]);
+    fn show_self(&self);
+    fn hide_self(&self);
+    fn set_selectable(&self, selectable: bool);
+    /// Moves and resizes the plugin's own floating pane.
+    fn change_floating_pane_coordinates(&self, rect: PaneRect);
+    fn switch_session(&self, name: &str);
+    fn switch_session_with_cwd(&self, name: &str, cwd: PathBuf);
+    fn switch_tab_to(&self, tab_idx: u32);
+    fn kill_sessions(&self, names: &[String]);
+    fn switch_to_input_mode(&self, mode: InputMode);
+    fn set_timeout(&self, secs: f64);
+    fn run_command(&self, command: &[&str], context: BTreeMap<String, String>);
+    fn run_command_with_env(
+        &self,
+        command: &[&str],
+        env_variables: BTreeMap<String, String>,
+        context: BTreeMap<String, String>,
+    );
+    fn cli_pipe_output(&self, pipe_id: &str, output: &str);
+    fn unblock_cli_pipe_input(&self, pipe_id: &str);
+}
+
+// Native test builds can't link the host functions, so they only get
+// `testing::RecordingHost`
+#[cfg(not(test))]
+pub struct ZellijHost;
+
+#[cfg(not(test))]
+impl Host for ZellijHost {
+    fn request_permission(&self, permissions: &[PermissionType]) {
+        request_permission(permissions);
+    }
+
+    fn subscribe(&self, event_types: &[EventType]) {
+        subscribe(event_types);
+    }
+
+    fn show_self(&self) {
+        show_self(true);
+    }
+
+    fn hide_self(&self) {
+        hide_self();
+    }
+
+    fn set_selectable(&self, selectable: bool) {
+        set_selectable(selectable);
+    }
+
+    fn change_floating_pane_coordinates(&self, rect: PaneRect) {
+        let coordinates = FloatingPaneCoordinates::new(
+            Some(rect.x.to_string()),
+            Some(rect.y.to_string()),
+            Some(rect.width.to_string()),
+            Some(rect.height.to_string()),
+            None,
+        );
+        if let Some(coordinates) = coordinates {
+            let pane_id = PaneId::Plugin(get_plugin_ids().plugin_id);
+            change_floating_panes_coordinates(vec![(pane_id, coordinates)]);
+        }
+    }
+
+    fn switch_session(&self, name: &str) {
+        switch_session(Some(name));
+    }
+
+    fn switch_session_with_cwd(&self, name: &str, cwd: PathBuf) {
+        switch_session_with_cwd(Some(name), Some(cwd));
+    }
+
+    fn switch_tab_to(&self, tab_idx: u32) {
+        switch_tab_to(tab_idx);
+    }
+
+    fn kill_sessions(&self, names: &[String]) {
+        kill_sessions(names);
+    }
+
+    fn switch_to_input_mode(&self, mode: InputMode) {
+        switch_to_input_mode(&mode);
+    }
+
+    fn set_timeout(&self, secs: f64) {
+        set_timeout(secs);
+    }
+
+    fn run_command(&self, command: &[&str], context: BTreeMap<String, String>) {
+        run_command(command, context);
+    }
+
+    fn run_command_with_env(
+        &self,
+        command: &[&str],
+        env_variables: BTreeMap<String, String>,
+        context: BTreeMap<String, String>,
+    ) {
+        run_command_with_env_variables_and_cwd(command, env_variables, PathBuf::from("."), context);
+    }
+
+    fn cli_pipe_output(&self, pipe_id: &str, output: &str) {
+        cli_pipe_output(pipe_id, output);
+    }
+
+    fn unblock_cli_pipe_input(&self, pipe_id: &str) {
+        unblock_cli_pipe_input(pipe_id);
+    }
+}
+
+// `register_plugin!` builds the plugin with `Default`, which has to pick the
+// real host
+impl Default for Box<dyn Host> {
+    #[cfg(not(test))]
+    fn default() -> Self {
+        Box::new(ZellijHost)
+    }
+
+    #[cfg(test)]
+    fn default() -> Self {
+        Box::new(testing::RecordingHost::default())
+    }
+}
+
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum HostCall {
+        RequestPermission(Vec<PermissionType>),
+        Subscribe(Vec<EventType>),
+        ShowSelf,
+        HideSelf,
+        SetSelectable(bool),
+        ChangeFloatingPaneCoordinates(PaneRect),
+        SwitchSession(String),
+        SwitchSessionWithCwd(String, PathBuf),
+        SwitchTabTo(u32),
+        KillSessions(Vec<String>),
+        SwitchToInputMode(InputMode),
+        SetTimeout(f64),
+        RunCommand {
+            command: Vec<String>,
+            env_variables: BTreeMap<String, String>,
+            context: BTreeMap<String, String>,
+        },
+        CliPipeOutput(String, String),
+        UnblockCliPipeInput(String),
+    }
+
+    /// Records every host call instead of performing it. Clones share the
+    /// same record, so a test can keep one while the manager owns another.
+    #[derive(Default, Clone)]
+    pub struct RecordingHost {
+        calls: Rc<RefCell<Vec<HostCall>>>,
+    }
+
+    impl RecordingHost {
+        pub fn calls(&self) -> Vec<HostCall> {
+            self.calls.borrow().clone()
+        }
+
+        pub fn switched_sessions(&self) -> Vec<String> {
+            self.calls()
+                .into_iter()
+                .filter_map(|call| match call {
+                    HostCall::SwitchSession(name) | HostCall::SwitchSessionWithCwd(name, _) => {
+                        Some(name)
+                    }
+                    _ => None,
+                })
+                .collect()
+        }
+
+        fn record(&self, call: HostCall) {
+            self.calls.borrow_mut().push(call);
+        }
+    }
+
+    impl Host for RecordingHost {
+        fn request_permission(&self, permissions: &[PermissionType]) {
+            self.record(HostCall::RequestPermission(permissions.to_vec()));
+        }
+
+        fn subscribe(&self, event_types: &[EventType]) {
+            self.record(HostCall::Subscribe(event_types.to_vec()));
+        }
+
+        fn show_self(&self) {
+            self.record(HostCall::ShowSelf);
+        }
+
+        fn hide_self(&self) {
+            self.record(HostCall::HideSelf);
+        }
+
+        fn set_selectable(&self, selectable: bool) {
+            self.record(HostCall::SetSelectable(selectable));
+        }
+
+        fn change_floating_pane_coordinates(&self, rect: PaneRect) {
+            self.record(HostCall::ChangeFloatingPaneCoordinates(rect));
+        }
+
+        fn switch_session(&self, name: &str) {
+            self.record(HostCall::SwitchSession(name.to_string()));
+        }
+
+        fn switch_session_with_cwd(&self, name: &str, cwd: PathBuf) {
+            self.record(HostCall::SwitchSessionWithCwd(name.to_string(), cwd));
+        }
+
+        fn switch_tab_to(&self, tab_idx: u32) {
+            self.record(HostCall::SwitchTabTo(tab_idx));
+        }
+
+        fn kill_sessions(&self, names: &[String]) {
+            self.record(HostCall::KillSessions(names.to_vec()));
+        }
+
+        fn switch_to_input_mode(&self, mode: InputMode) {
+            self.record(HostCall::SwitchToInputMode(mode));
+        }
+
+        fn set_timeout(&self, secs: f64) {
+            self.record(HostCall::SetTimeout(secs));
+        }
+
+        fn run_command(&self, command: &[&str], context: BTreeMap<String, String>) {
+            self.run_command_with_env(command, BTreeMap::new(), context);
+        }
+
+        fn run_command_with_env(
+            &self,
+            command: &[&str],
+            env_variables: BTreeMap<String, String>,
+            context: BTreeMap<String, String>,
+        ) {
+            self.record(HostCall::RunCommand {
+                command: command.iter().map(|part| part.to_string()).collect(),
+                env_variables,
+                context,
+            });
+        }
+
+        fn cli_pipe_output(&self, pipe_id: &str, output: &str) {
+            self.record(HostCall::CliPipeOutput(
+                pipe_id.to_string(),
+                output.to_string(),
+            ));
+        }
+
+        fn unblock_cli_pipe_input(&self, pipe_id: &str) {
+            self.record(HostCall::UnblockCliPipeInput(pipe_id.to_string()));
+        }
+    }
+}
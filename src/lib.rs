@@ -0,0 +1,6 @@
+//! The parts of the plugin that don't need zellij, shared with the
+//! `zellij-hotbar` command line tool so both read and write the same format.
+
+pub mod dates;
+pub mod error;
+pub mod persistence;
@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::persistence;
+
+pub const LOG_LEVEL_KEY: &str = "log_level";
+// Enough to see what led up to a failed switch without growing unbounded
+const LOG_CAPACITY: usize = 200;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum LogLevel {
+    /// Internal state, e.g. every load of the data file
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "debug" => LogLevel::Debug,
+            "warn" => LogLevel::Warn,
+            "error" => LogLevel::Error,
+            _ => LogLevel::Info,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub timestamp: u64,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+/// Writes messages at or above the configured `log_level` to the zellij log
+/// and keeps the most recent ones for the log screen.
+#[derive(Default)]
+pub struct Log {
+    pub level: LogLevel,
+    entries: VecDeque<LogEntry>,
+}
+
+impl Log {
+    pub fn from_configuration(configuration: &BTreeMap<String, String>) -> Self {
+        Self {
+            level: configuration
+                .get(LOG_LEVEL_KEY)
+                .map(|value| LogLevel::from_config(value))
+                .unwrap_or_default(),
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn log(&mut self, level: LogLevel, message: impl Into<String>) {
+        if level < self.level {
+            return;
+        }
+
+        let message = message.into();
+        // Plugins' stderr ends up in the zellij log, stdout would go to the pane
+        eprintln!("[{}] {}", level.label(), message);
+        if self.entries.len() == LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(LogEntry {
+            timestamp: persistence::unix_now(),
+            level,
+            message,
+        });
+    }
+
+    pub fn debug(&mut self, message: impl Into<String>) {
+        self.log(LogLevel::Debug, message);
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.log(LogLevel::Info, message);
+    }
+
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.log(LogLevel::Warn, message);
+    }
+
+    /// The kept entries, oldest first.
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = &LogEntry> {
+        self.entries.iter()
+    }
+
+    #[cfg(test)]
+    pub fn entries_mut(&mut self) -> impl Iterator<Item = &mut LogEntry> {
+        self.entries.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_by_level_and_keeps_recent_entries() {
+        let mut configuration = BTreeMap::new();
+        configuration.insert(LOG_LEVEL_KEY.to_string(), "warn".to_string());
+        let mut log = Log::from_configuration(&configuration);
+
+        log.info("ignored");
+        for i in 0..LOG_CAPACITY + 5 {
+            log.warn(format!("warning {}", i));
+        }
+        log.log(LogLevel::Error, "last");
+
+        assert_eq!(log.entries().len(), LOG_CAPACITY);
+        assert_eq!(log.entries().next().unwrap().message, "warning 6");
+        let last = log.entries().last().unwrap();
+        assert_eq!(
+            (last.level, last.message.as_str()),
+            (LogLevel::Error, "last")
+        );
+    }
+}
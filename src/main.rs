@@ -1,24 +1,64 @@
-mod persistence;
+mod auto_assign;
+mod bindings;
+mod discovery;
+mod filter;
+mod geometry;
+mod grouping;
+mod hooks;
+mod host;
+mod logging;
+mod notify;
+mod screen;
 mod session_manager;
+mod switcher;
 mod ui;
+mod usage;
 
 use session_manager::HotbarManager;
 use std::collections::BTreeMap;
+use zellij_hotbar_manager::{dates, error, persistence};
 use zellij_tile::prelude::*;
 
+#[cfg(not(test))]
 register_plugin!(HotbarManager);
 
 const MODE_SWITCH_COMMAND: &str = "hotbar_switch_mode";
 const OPEN_RECENT: &str = "open_recent_hotbar";
 const SWITCH_SLOT_PREFIX: &str = "switch_slot_";
 const TOGGLE_UI: &str = "toggle_ui";
+const UNDO: &str = "hotbar_undo";
+const REDO: &str = "hotbar_redo";
+const NEXT_SLOT: &str = "hotbar_next";
+const PREVIOUS_SLOT: &str = "hotbar_prev";
+const SWITCHER: &str = "hotbar_switcher";
+const RESTORE_BACKUP: &str = "restore_backup";
+const USAGE_REPORT: &str = "usage_report";
+const GENERATE_BINDINGS: &str = "generate_bindings";
 
 impl ZellijPlugin for HotbarManager {
     fn load(&mut self, configuration: BTreeMap<String, String>) {
-        request_permission(&[
-            PermissionType::ChangeApplicationState,
-            PermissionType::ReadApplicationState,
-        ]);
+        self.request_permissions();
+
+        self.log = logging::Log::from_configuration(&configuration);
+        self.discovery = discovery::DiscoveryConfig::from_configuration(&configuration);
+        self.auto_assign =
+            auto_assign::AutoAssignConfig::from_configuration(&configuration, &mut self.log);
+        self.hooks = hooks::HookConfig::from_configuration(&configuration);
+        self.notifications = notify::NotificationConfig::from_configuration(&configuration);
+        self.switcher = switcher::Switcher::from_configuration(&configuration);
+        self.geometry = geometry::ModalGeometry::from_configuration(&configuration);
+        self.group_by = configuration
+            .get(grouping::GROUP_BY_KEY)
+            .map(|value| grouping::GroupBy::from_config(value))
+            .unwrap_or_default();
+        self.select_on_open = configuration
+            .get(session_manager::SELECT_ON_OPEN_KEY)
+            .map(|value| session_manager::OpenSelection::from_config(value))
+            .unwrap_or_default();
+        self.group_separator = configuration
+            .get(grouping::GROUP_SEPARATOR_KEY)
+            .cloned()
+            .unwrap_or_else(|| grouping::DEFAULT_GROUP_SEPARATOR.to_string());
 
         for (key, value) in configuration {
             if key.starts_with(session_manager::PREFIX_KEY) {
@@ -28,18 +68,31 @@ impl ZellijPlugin for HotbarManager {
 
         self.load_hotbar_data();
 
-        subscribe(&[EventType::SessionUpdate, EventType::Key, EventType::Visible]);
+        self.host.subscribe(&[
+            EventType::SessionUpdate,
+            EventType::Key,
+            EventType::Visible,
+            EventType::RunCommandResult,
+            EventType::Timer,
+            EventType::PermissionRequestResult,
+            EventType::TabUpdate,
+        ]);
+
+        discovery::start_scan(self.host.as_ref(), &self.discovery);
+        // Arms the timer that periodically saves focus time
+        self.on_timer();
 
         // Plugin starts as headless background service
         self.is_visible = false;
-        set_selectable(false);
+        self.host.set_selectable(false);
     }
 
     fn pipe(&mut self, message: PipeMessage) -> bool {
         let name = message.name.as_str();
 
         if name == TOGGLE_UI {
-            if self.is_visible {
+            // A notification shown on its own is replaced by the modal
+            if self.is_visible && !self.showing_toast {
                 self.hide_ui();
             } else {
                 self.show_ui();
@@ -47,24 +100,83 @@ impl ZellijPlugin for HotbarManager {
             return true; // Request a re-render
         }
 
-        if let Some(slot_str) = name.strip_prefix(SWITCH_SLOT_PREFIX)
-            && let Ok(slot @ 1..=5) = slot_str.parse::<usize>()
-        {
-            // Reload hotbar data to get latest assignments from other plugin instances
-            self.load_hotbar_data();
-            self.switch_to_slot(slot - 1);
+        if let Some(slot_str) = name.strip_prefix(SWITCH_SLOT_PREFIX) {
+            let result = match slot_str.parse::<usize>() {
+                Ok(slot @ 1..=5) => {
+                    // Reload hotbar data to get latest assignments from other plugin instances
+                    self.load_hotbar_data();
+                    self.switch_to_slot(slot - 1)
+                }
+                _ => Err(error::HotbarError::InvalidSlot(slot_str.to_string())),
+            };
+            self.report_to(&message.source, result);
             return false;
         }
 
-        if name.starts_with(session_manager::PREFIX_KEY) {
+        if name == SWITCHER {
+            let result = self.trigger_switcher();
+            self.report_to(&message.source, result);
+            return true;
+        }
+
+        if name == NEXT_SLOT || name == PREVIOUS_SLOT {
             self.load_hotbar_data();
-            self.switch_to(name);
+            let direction = if name == NEXT_SLOT { 1 } else { -1 };
+            let result = self.cycle_slots(direction);
+            self.report_to(&message.source, result);
             return false;
         }
 
         if name == OPEN_RECENT {
             self.load_hotbar_data();
-            self.switch_to_previous_session();
+            let result = self.switch_to_previous_session();
+            self.report_to(&message.source, result);
+            return false;
+        }
+
+        if name == UNDO || name == REDO {
+            // Journal lives in the data file, so edits made from any session can be undone
+            self.load_hotbar_data();
+            let result = if name == UNDO {
+                self.undo()
+            } else {
+                self.redo()
+            };
+            self.report_to(&message.source, result);
+            return self.is_visible;
+        }
+
+        if name == RESTORE_BACKUP {
+            // Payload selects the backup, 0 (the default) being the newest
+            let index = message
+                .payload
+                .as_deref()
+                .and_then(|payload| payload.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            let result = self.restore_backup(index);
+            self.report_to(&message.source, result);
+            return self.is_visible;
+        }
+
+        if name == USAGE_REPORT {
+            let result = self.flush_focus_time();
+            self.report(result);
+            self.load_hotbar_data();
+            let today = dates::day_of(persistence::unix_now());
+            let report = match usage::ReportOptions::from_args(&message.args, today) {
+                Ok(options) => usage::report(&self.hotbar_data, &options),
+                Err(e) => format!("{}\n", e),
+            };
+            self.reply(&message.source, &report);
+            return false;
+        }
+
+        if name == GENERATE_BINDINGS {
+            let output = match bindings::BindingOptions::from_args(&message.args) {
+                Ok(options) => bindings::generate_all(&options),
+                Err(e) => format!("{}\n", e),
+            };
+            self.reply(&message.source, &output);
             return false;
         }
 
@@ -78,6 +190,14 @@ impl ZellijPlugin for HotbarManager {
             return false;
         }
 
+        // Last, as the other `hotbar_*` commands share the prefix of the keys
+        if name.starts_with(session_manager::PREFIX_KEY) {
+            self.load_hotbar_data();
+            let result = self.switch_to(name);
+            self.report_to(&message.source, result);
+            return false;
+        }
+
         false
     }
 
@@ -85,7 +205,10 @@ impl ZellijPlugin for HotbarManager {
         match event {
             Event::Visible(is_visible) => {
                 self.is_visible = is_visible;
-                set_selectable(is_visible);
+                if !is_visible {
+                    self.showing_toast = false;
+                }
+                self.host.set_selectable(is_visible && !self.showing_toast);
                 true
             }
             Event::SessionUpdate(sessions, _) => {
@@ -94,9 +217,138 @@ impl ZellijPlugin for HotbarManager {
                     .into_iter()
                     .find(|s| s.is_current_session)
                     .map(|session| self.set_active_session(&session.name));
+                self.update_focus_tracking();
+                let result = self.apply_auto_assign();
+                self.report(result);
                 false
             }
+            Event::TabUpdate(tabs) => {
+                if let Some(tab) = tabs.iter().find(|tab| tab.active) {
+                    self.set_viewport(tab.viewport_rows, tab.viewport_columns);
+                }
+                false
+            }
+            Event::RunCommandResult(exit_code, stdout, stderr, context)
+                if discovery::is_scan_result(&context) =>
+            {
+                if exit_code != Some(0) {
+                    self.log.warn(format!(
+                        "Project scan exited with {:?}: {}",
+                        exit_code,
+                        String::from_utf8_lossy(&stderr)
+                    ));
+                }
+                self.set_projects(discovery::parse_scan_output(&stdout));
+                self.is_visible
+            }
+            Event::RunCommandResult(exit_code, _stdout, stderr, context) => {
+                if let Some(hook) = hooks::parse_hook_result(&context) {
+                    self.handle_hook_result(exit_code, &stderr, hook);
+                }
+                false
+            }
+            Event::PermissionRequestResult(status) => {
+                self.set_permission_status(status);
+                true
+            }
+            // The modal explains what was denied instead of listing sessions
+            Event::Key(key) if self.permissions == session_manager::PermissionState::Denied => {
+                match key.bare_key {
+                    BareKey::Char('r') => self.request_permissions(),
+                    BareKey::Esc | BareKey::Char('q') => self.hide_ui(),
+                    _ => return false,
+                }
+                true
+            }
+            Event::Timer(_) => {
+                let expired = self.expire_notification();
+                let switched = self.commit_switcher_if_due();
+                let hook_timed_out = self.expire_waiting_switch();
+                self.on_timer();
+                expired || switched || hook_timed_out
+            }
+            Event::Key(key) if self.modal_mode == session_manager::ModalMode::Switcher => {
+                match key.bare_key {
+                    BareKey::Down | BareKey::Tab => self.move_switcher(1),
+                    BareKey::Up => self.move_switcher(-1),
+                    BareKey::Enter => self.commit_switcher(),
+                    BareKey::Esc | BareKey::Char('q') => self.hide_ui(),
+                    _ => return false,
+                }
+                true
+            }
+            Event::Key(key) if self.modal_mode == session_manager::ModalMode::Stats => {
+                match key.bare_key {
+                    BareKey::Esc | BareKey::Char('q') => {
+                        self.modal_mode = session_manager::ModalMode::Normal
+                    }
+                    _ => return false,
+                }
+                true
+            }
+            Event::Key(key) if self.is_typing() => {
+                let result = self.handle_text_key(&key);
+                self.report(result);
+                true
+            }
+            Event::Key(key) if self.is_confirming() => {
+                if key.bare_key == BareKey::Char('y') {
+                    let result = self.confirm_pending_change();
+                    self.report(result);
+                } else {
+                    self.cancel_pending_change();
+                }
+                true
+            }
+            Event::Key(key) if self.modal_mode == session_manager::ModalMode::Bindings => {
+                match key.bare_key {
+                    BareKey::Up => self.scroll_bindings(-1),
+                    BareKey::Down => self.scroll_bindings(1),
+                    BareKey::Left => self.cycle_bindings_format(-1),
+                    BareKey::Right | BareKey::Tab => self.cycle_bindings_format(1),
+                    BareKey::Esc | BareKey::Char('q') => {
+                        self.modal_mode = session_manager::ModalMode::Normal
+                    }
+                    _ => return false,
+                }
+                true
+            }
+            Event::Key(key) if self.modal_mode == session_manager::ModalMode::Log => {
+                match key.bare_key {
+                    BareKey::Up => self.scroll_log(-1),
+                    BareKey::Down => self.scroll_log(1),
+                    BareKey::Esc | BareKey::Char('q') => {
+                        self.modal_mode = session_manager::ModalMode::Normal
+                    }
+                    _ => return false,
+                }
+                true
+            }
+            Event::Key(key) if self.modal_mode == session_manager::ModalMode::RestoreBackup => {
+                match key.bare_key {
+                    BareKey::Up => self.navigate_backups(-1),
+                    BareKey::Down => self.navigate_backups(1),
+                    // Closed first, as restoring may ask to confirm a locked slot
+                    BareKey::Enter => {
+                        let index = self.backup_index;
+                        self.close_backups();
+                        let result = self.restore_backup(index);
+                        self.report(result);
+                    }
+                    BareKey::Esc | BareKey::Char('q') => self.close_backups(),
+                    _ => return false,
+                }
+                true
+            }
             Event::Key(key) => match key.bare_key {
+                BareKey::Esc if !self.marked.is_empty() => {
+                    self.marked.clear();
+                    true
+                }
+                BareKey::Esc if !self.filter.is_empty() => {
+                    self.clear_filter();
+                    true
+                }
                 BareKey::Esc => {
                     self.hide_ui();
                     true
@@ -109,39 +361,120 @@ impl ZellijPlugin for HotbarManager {
                     self.navigate_sessions(1);
                     true
                 }
+                BareKey::Left => {
+                    self.collapse_selected_group();
+                    true
+                }
+                BareKey::Right => {
+                    self.expand_selected_group();
+                    true
+                }
+                BareKey::Char('g') => {
+                    self.cycle_group_by();
+                    true
+                }
                 BareKey::Char('q') => {
                     self.hide_ui();
                     true
                 }
+                BareKey::Char(' ') => {
+                    self.toggle_mark();
+                    true
+                }
+                BareKey::Char('x') if !self.marked.is_empty() => {
+                    self.remove_marked();
+                    true
+                }
+                BareKey::Char('t') if !self.marked.is_empty() => {
+                    self.start_tag_marked();
+                    true
+                }
+                BareKey::Char(digit @ '1'..='5') if !self.marked.is_empty() => {
+                    self.assign_marked_from(digit as usize - '1' as usize);
+                    true
+                }
+                BareKey::Char('K') => {
+                    self.kill_marked();
+                    true
+                }
                 BareKey::Char('x') => {
-                    self.remove_selected_from_hotbar();
+                    let result = self.remove_selected_from_hotbar();
+                    self.report(result);
+                    true
+                }
+                BareKey::Char('r') if key.has_modifiers(&[KeyModifier::Ctrl]) => {
+                    let result = self.redo();
+                    self.report(result);
+                    true
+                }
+                BareKey::Char('u') => {
+                    let result = self.undo();
+                    self.report(result);
+                    true
+                }
+                BareKey::Char('/') => {
+                    self.start_filter();
+                    true
+                }
+                BareKey::Char('n') => {
+                    self.start_edit_note();
+                    true
+                }
+                BareKey::Char('t') => {
+                    self.start_edit_tags();
+                    true
+                }
+                BareKey::Char('s') => {
+                    self.open_stats();
+                    true
+                }
+                BareKey::Char('b') => {
+                    self.open_backups();
+                    true
+                }
+                BareKey::Char('k') => {
+                    self.open_bindings();
+                    true
+                }
+                BareKey::Char('L') => {
+                    self.open_log();
+                    true
+                }
+                BareKey::Char('p') => {
+                    let result = self.toggle_selected_slot_lock();
+                    self.report(result);
                     true
                 }
                 BareKey::Char('1') => {
-                    self.assign_selected_to_slot(0);
+                    let result = self.assign_selected_to_slot(0);
+                    self.report(result);
                     true
                 }
                 BareKey::Char('2') => {
-                    self.assign_selected_to_slot(1);
+                    let result = self.assign_selected_to_slot(1);
+                    self.report(result);
                     true
                 }
                 BareKey::Char('3') => {
-                    self.assign_selected_to_slot(2);
+                    let result = self.assign_selected_to_slot(2);
+                    self.report(result);
                     true
                 }
                 BareKey::Char('4') => {
-                    self.assign_selected_to_slot(3);
+                    let result = self.assign_selected_to_slot(3);
+                    self.report(result);
                     true
                 }
                 BareKey::Char('5') => {
-                    self.assign_selected_to_slot(4);
+                    let result = self.assign_selected_to_slot(4);
+                    self.report(result);
                     true
                 }
                 BareKey::Enter => {
                     if let Some(session) = self.get_selected_session() {
                         let session = session.clone();
                         self.hide_ui();
-                        switch_session(Some(&session));
+                        self.open_session(&session);
                     }
                     true
                 }
@@ -160,3 +493,134 @@ impl ZellijPlugin for HotbarManager {
         ui::render_hotbar_modal(self, rows, cols);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::testing::HostCall;
+    use crate::session_manager::tests::{session, test_manager};
+
+    fn cli_message(name: &str, args: &[(&str, &str)]) -> PipeMessage {
+        let args = args
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        PipeMessage::new(
+            PipeSource::Cli("pipe-1".to_string()),
+            name,
+            &None,
+            &Some(args),
+            false,
+        )
+    }
+
+    #[test]
+    fn toggle_ui_pipe_shows_and_hides() {
+        let (mut manager, _host) = test_manager("pipe_toggle");
+
+        assert!(manager.pipe(cli_message(TOGGLE_UI, &[])));
+        assert!(manager.is_visible);
+        assert!(manager.pipe(cli_message(TOGGLE_UI, &[])));
+        assert!(!manager.is_visible);
+    }
+
+    #[test]
+    fn switch_slot_pipe_uses_saved_assignments() {
+        let (mut manager, host) = test_manager("pipe_switch");
+        manager.update_session_list(vec![session("api"), session("docs")]);
+        manager.navigate_sessions(1);
+        manager.assign_selected_to_slot(3).unwrap();
+        // The pipe reloads the data file, so in-memory changes don't count
+        manager.hotbar_data = persistence::HotbarData::default();
+
+        assert!(!manager.pipe(cli_message("switch_slot_4", &[])));
+        assert!(!manager.pipe(cli_message("switch_slot_6", &[])));
+
+        assert_eq!(host.switched_sessions(), vec!["docs".to_string()]);
+    }
+
+    #[test]
+    fn switch_pipes_report_errors_to_the_cli() {
+        let (mut manager, host) = test_manager("pipe_errors");
+
+        manager.pipe(cli_message("switch_slot_3", &[]));
+        manager.pipe(cli_message("switch_slot_x", &[]));
+
+        assert_eq!(
+            host.calls()
+                .into_iter()
+                .filter(|call| matches!(call, HostCall::CliPipeOutput(..)))
+                .collect::<Vec<_>>(),
+            vec![
+                HostCall::CliPipeOutput(
+                    "pipe-1".to_string(),
+                    "No session saved in slot 3\n".to_string()
+                ),
+                HostCall::CliPipeOutput("pipe-1".to_string(), "Invalid slot: x\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn denied_permissions_fail_switches_until_requested_again() {
+        let (mut manager, host) = test_manager("pipe_permissions");
+        manager.update(Event::PermissionRequestResult(PermissionStatus::Denied));
+
+        manager.pipe(cli_message("switch_slot_1", &[]));
+        assert!(host.calls().contains(&HostCall::CliPipeOutput(
+            "pipe-1".to_string(),
+            "Permission denied: ChangeApplicationState\n".to_string()
+        )));
+
+        manager.update(Event::Key(KeyWithModifier::new(BareKey::Char('r'))));
+        assert_eq!(
+            manager.permissions,
+            session_manager::PermissionState::Pending
+        );
+        assert!(matches!(
+            host.calls().last(),
+            Some(HostCall::RequestPermission(_))
+        ));
+    }
+
+    #[test]
+    fn open_recent_pipe_switches_to_previous_session() {
+        let (mut manager, host) = test_manager("pipe_recent");
+        manager.hotbar_data.previous_session = Some("api".to_string());
+        manager.save_hotbar_data().unwrap();
+
+        manager.pipe(cli_message(OPEN_RECENT, &[]));
+
+        assert_eq!(host.switched_sessions(), vec!["api".to_string()]);
+    }
+
+    #[test]
+    fn undo_pipe_is_not_taken_for_a_key() {
+        let (mut manager, host) = test_manager("pipe_undo");
+        manager
+            .hotbar_data
+            .edit_slots(&[(1, Some("docs".to_string()))]);
+        manager.save_hotbar_data().unwrap();
+
+        manager.pipe(cli_message(UNDO, &[]));
+
+        assert_eq!(manager.hotbar_data.get_session_at_slot(1), None);
+        assert!(host.switched_sessions().is_empty());
+    }
+
+    #[test]
+    fn usage_report_pipe_answers_cli() {
+        let (mut manager, host) = test_manager("pipe_report");
+
+        manager.pipe(cli_message(USAGE_REPORT, &[("format", "csv")]));
+
+        let calls = host.calls();
+        assert!(matches!(
+            calls.as_slice(),
+            [
+                HostCall::CliPipeOutput(pipe_id, report),
+                HostCall::UnblockCliPipeInput(unblocked),
+            ] if pipe_id == "pipe-1" && unblocked == "pipe-1" && report == "date,session,seconds\n"
+        ));
+    }
+}
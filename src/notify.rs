@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+pub const NOTIFICATIONS_KEY: &str = "notifications";
+pub const NOTIFICATION_SECONDS_KEY: &str = "notification_seconds";
+const DEFAULT_NOTIFICATION_SECS: u64 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    /// Confirms something the user asked for, e.g. an assignment
+    Info,
+    /// Something the user asked for didn't happen
+    Error,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum Verbosity {
+    /// Messages only go to the log
+    Off,
+    Errors,
+    #[default]
+    All,
+}
+
+impl Verbosity {
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "off" | "none" => Verbosity::Off,
+            "errors" => Verbosity::Errors,
+            _ => Verbosity::All,
+        }
+    }
+}
+
+/// How notifications are shown, configured with `notifications`
+/// (`all`, `errors` or `off`) and `notification_seconds`.
+pub struct NotificationConfig {
+    pub verbosity: Verbosity,
+    pub duration_secs: u64,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            verbosity: Verbosity::default(),
+            duration_secs: DEFAULT_NOTIFICATION_SECS,
+        }
+    }
+}
+
+impl NotificationConfig {
+    pub fn from_configuration(configuration: &BTreeMap<String, String>) -> Self {
+        Self {
+            verbosity: configuration
+                .get(NOTIFICATIONS_KEY)
+                .map(|value| Verbosity::from_config(value))
+                .unwrap_or_default(),
+            duration_secs: configuration
+                .get(NOTIFICATION_SECONDS_KEY)
+                .and_then(|value| value.parse::<u64>().ok())
+                .filter(|secs| *secs > 0)
+                .unwrap_or(DEFAULT_NOTIFICATION_SECS),
+        }
+    }
+
+    pub fn shows(&self, level: Level) -> bool {
+        match self.verbosity {
+            Verbosity::Off => false,
+            Verbosity::Errors => level == Level::Error,
+            Verbosity::All => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    /// Unix time after which the notification is dismissed
+    pub expires_at: u64,
+}
@@ -1,56 +1,464 @@
 use rkyv::{Archive, Deserialize, Serialize};
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-fn get_hotbar_file_path() -> PathBuf {
-    PathBuf::from("/tmp/zellij-hotbar-manager.rkyv")
+use crate::dates;
+use crate::error::HotbarError;
+
+const MAX_BACKUPS: usize = 10;
+// Saved files start with the magic and the format version (little endian),
+// followed by the archive. Bump the version whenever `HotbarData`'s layout
+// changes, keeping a copy of the old layout to migrate from. Files without the
+// header are version 0, see `HotbarDataV0`; version 1 is `HotbarDataV1`.
+const MAGIC: &[u8; 4] = b"ZHBM";
+pub const FORMAT_VERSION: u32 = 2;
+const HEADER_LEN: usize = 8;
+
+pub struct Backup {
+    pub path: PathBuf,
+    pub timestamp: u64,
+    /// Orders backups taken within the same second
+    sequence: u32,
+    pub data: HotbarData,
+}
+
+/// The file the hotbar data is saved to. Backups live next to it, named
+/// after it with the unix time of the snapshot appended
+/// (e.g. `zellij-hotbar-manager.rkyv.1760000000`), and a counter when the
+/// second already has one (`.1760000000-1`). A damaged file is never saved
+/// over but moved aside to `<name>.corrupt-<unix millis>`, and one written in
+/// another format version isn't saved over at all.
+#[derive(Debug, Clone)]
+pub struct DataFile {
+    path: PathBuf,
+}
+
+/// Name of the data file in the plugin's `/tmp`, which Zellij maps to
+/// `$TMPDIR/zellij-<uid>` on the host.
+pub const DATA_FILE_NAME: &str = "zellij-hotbar-manager.rkyv";
+
+// The path as the plugin sees it
+impl Default for DataFile {
+    fn default() -> Self {
+        Self::new(Path::new("/tmp").join(DATA_FILE_NAME))
+    }
+}
+
+impl DataFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// A data file in a fresh directory of its own under the system's temp
+    /// directory, for tests. `name` keeps the tests of one run apart, the
+    /// process id separate runs. Not `cfg(test)`, as the plugin's and the
+    /// command line tool's tests build this crate without it.
+    pub fn temp(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("zellij-hotbar-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("a temp directory for the data file");
+        Self::new(dir.join("hotbar.rkyv"))
+    }
+
+    fn backup_path(&self, timestamp: u64, sequence: u32) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        match sequence {
+            0 => path.push(format!(".{}", timestamp)),
+            _ => path.push(format!(".{}-{}", timestamp, sequence)),
+        }
+        PathBuf::from(path)
+    }
+
+    fn free_backup_path(&self, timestamp: u64) -> PathBuf {
+        (0..)
+            .map(|sequence| self.backup_path(timestamp, sequence))
+            .find(|path| !path.exists())
+            .expect("a free backup name")
+    }
+
+    fn corrupt_path(&self, timestamp_ms: u64) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".corrupt-{}", timestamp_ms));
+        PathBuf::from(path)
+    }
+
+    pub fn load(&self) -> Result<HotbarData, HotbarError> {
+        HotbarData::load_from_path(&self.path)
+    }
+
+    pub fn save(&self, data: &HotbarData) -> Result<(), HotbarError> {
+        self.save_at(data, unix_now())
+    }
+
+    fn save_at(&self, data: &HotbarData, now: u64) -> Result<(), HotbarError> {
+        let archive = rkyv::to_bytes::<_, 256>(data)
+            .map_err(|e| HotbarError::CorruptArchive(e.to_string()))?;
+        let mut bytes = Vec::with_capacity(HEADER_LEN + archive.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&archive);
+
+        // Snapshot the file about to be replaced whenever its slots differ from
+        // the new ones, so metadata-only saves don't push real backups out
+        let mut backed_up = false;
+        match self.load() {
+            Ok(current) => {
+                if current.favorites != data.favorites {
+                    std::fs::copy(&self.path, self.free_backup_path(now))?;
+                    backed_up = true;
+                }
+            }
+            Err(HotbarError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+            // Kept for the user to recover, the data can't be read anyway
+            Err(HotbarError::CorruptArchive(_)) => {
+                std::fs::rename(&self.path, self.corrupt_path(unix_now_millis()))?
+            }
+            // E.g. written by a newer version still running elsewhere, which
+            // isn't ours to replace
+            Err(e) => return Err(e),
+        }
+
+        // Write to a temporary file and rename it over the data file, so a
+        // concurrent reader never sees a half-written archive
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, &bytes)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+
+        // Only once the data is safe, as failing to prune fails the save
+        if backed_up {
+            self.prune_backups()?;
+        }
+        Ok(())
+    }
+
+    /// Lists the readable backups, newest first.
+    pub fn list_backups(&self) -> Vec<Backup> {
+        let mut backups: Vec<Backup> = self
+            .backup_files()
+            .into_iter()
+            .filter_map(|(path, timestamp, sequence)| {
+                let data = HotbarData::load_from_path(&path).ok()?;
+                Some(Backup {
+                    path,
+                    timestamp,
+                    sequence,
+                    data,
+                })
+            })
+            .collect();
+
+        backups.sort_by_key(|backup| std::cmp::Reverse((backup.timestamp, backup.sequence)));
+        backups
+    }
+
+    /// The files named like backups that can't be read, with the reason.
+    pub fn unreadable_backups(&self) -> Vec<(PathBuf, HotbarError)> {
+        self.backup_files()
+            .into_iter()
+            .filter_map(|(path, _, _)| {
+                let error = HotbarData::load_from_path(&path).err()?;
+                Some((path, error))
+            })
+            .collect()
+    }
+
+    // Every file named like a backup, with its timestamp and sequence number
+    fn backup_files(&self) -> Vec<(PathBuf, u64, u32)> {
+        let (Some(dir), Some(file_name)) = (self.path.parent(), self.path.file_name()) else {
+            return Vec::new();
+        };
+        let prefix = format!("{}.", file_name.to_string_lossy());
+
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                let suffix = name.to_str()?.strip_prefix(&prefix)?;
+                let (timestamp, sequence) = match suffix.split_once('-') {
+                    Some((timestamp, sequence)) => (timestamp, sequence.parse().ok()?),
+                    None => (suffix, 0),
+                };
+                Some((entry.path(), timestamp.parse().ok()?, sequence))
+            })
+            .collect()
+    }
+
+    fn prune_backups(&self) -> Result<(), HotbarError> {
+        for backup in self.list_backups().into_iter().skip(MAX_BACKUPS) {
+            std::fs::remove_file(&backup.path)?;
+        }
+        Ok(())
+    }
 }
 
-#[derive(Archive, Deserialize, Serialize, Debug, Clone)]
+#[derive(
+    Archive, Deserialize, Serialize, SerdeDeserialize, SerdeSerialize, Debug, Clone, Default,
+)]
+#[archive(check_bytes)]
 #[archive_attr(derive(Debug))]
+#[serde(default)]
 pub struct SessionMetadata {
     pub last_accessed: u64,
     pub tab_count: u64,
+    pub access_count: u64,
+    pub note: String,
+    pub tags: Vec<String>,
 }
 
-#[derive(Archive, Deserialize, Serialize, Debug, Clone)]
+/// One slot's contents before and after an edit.
+#[derive(Archive, Deserialize, Serialize, SerdeDeserialize, SerdeSerialize, Debug, Clone)]
+#[archive(check_bytes)]
 #[archive_attr(derive(Debug))]
+pub struct SlotChange {
+    pub slot: usize,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// A user edit of the hotbar (assign, remove, ...), undone and redone as a
+/// whole even when it touches several slots.
+#[derive(Archive, Deserialize, Serialize, SerdeDeserialize, SerdeSerialize, Debug, Clone)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+pub struct HotbarEdit {
+    pub changes: Vec<SlotChange>,
+}
+
+impl HotbarEdit {
+    /// The slot contents undoing the edit puts back.
+    pub fn reverted(&self) -> Vec<(usize, Option<String>)> {
+        self.changes
+            .iter()
+            .map(|change| (change.slot, change.before.clone()))
+            .collect()
+    }
+
+    /// The slot contents redoing the edit puts back.
+    pub fn applied(&self) -> Vec<(usize, Option<String>)> {
+        self.changes
+            .iter()
+            .map(|change| (change.slot, change.after.clone()))
+            .collect()
+    }
+}
+
+const MAX_JOURNAL_LEN: usize = 50;
+
+/// `SessionMetadata` as saved in format version 0.
+#[derive(Archive, Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+#[archive(check_bytes)]
+struct SessionMetadataV0 {
+    last_accessed: u64,
+    tab_count: u64,
+}
+
+/// `HotbarData` as saved before the header was introduced: a bare archive of
+/// the slots, the previous session and per-session metadata.
+#[derive(Archive, Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+#[archive(check_bytes)]
+struct HotbarDataV0 {
+    favorites: Vec<Option<String>>,
+    previous_session: Option<String>,
+    metadata: HashMap<String, SessionMetadataV0>,
+}
+
+impl From<HotbarDataV0> for HotbarData {
+    fn from(data: HotbarDataV0) -> Self {
+        let metadata = data
+            .metadata
+            .into_iter()
+            .map(|(session, metadata)| {
+                let metadata = SessionMetadata {
+                    last_accessed: metadata.last_accessed,
+                    tab_count: metadata.tab_count,
+                    ..Default::default()
+                };
+                (session, metadata)
+            })
+            .collect();
+        Self {
+            favorites: data.favorites,
+            previous_session: data.previous_session,
+            metadata,
+            ..Default::default()
+        }
+    }
+}
+
+/// `HotbarData` as saved in format version 1, before explicitly cleared
+/// slots were recorded.
+#[derive(Archive, Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+#[archive(check_bytes)]
+struct HotbarDataV1 {
+    favorites: Vec<Option<String>>,
+    auto_assigned: Vec<bool>,
+    locked: Vec<bool>,
+    previous_session: Option<String>,
+    metadata: HashMap<String, SessionMetadata>,
+    undo_journal: Vec<HotbarEdit>,
+    redo_journal: Vec<HotbarEdit>,
+    focus_time: HashMap<String, HashMap<u64, u64>>,
+}
+
+impl From<HotbarDataV1> for HotbarData {
+    fn from(data: HotbarDataV1) -> Self {
+        Self {
+            favorites: data.favorites,
+            auto_assigned: data.auto_assigned,
+            locked: data.locked,
+            previous_session: data.previous_session,
+            metadata: data.metadata,
+            undo_journal: data.undo_journal,
+            redo_journal: data.redo_journal,
+            focus_time: data.focus_time,
+            ..Default::default()
+        }
+    }
+}
+
+// Besides the rkyv archive the plugin saves, the data can be exported to and
+// imported from JSON by the command line tool. Fields missing from the JSON
+// take their defaults, so older exports still import.
+#[derive(Archive, Deserialize, Serialize, SerdeDeserialize, SerdeSerialize, Debug, Clone)]
+#[archive(check_bytes)]
+#[archive_attr(derive(Debug))]
+#[serde(default)]
 pub struct HotbarData {
     pub favorites: Vec<Option<String>>,
+    /// Slots filled by auto-assign rules rather than by the user
+    pub auto_assigned: Vec<bool>,
+    /// Slots that can only be changed after an explicit confirmation
+    pub locked: Vec<bool>,
+    /// Slots the user emptied, which auto-assign leaves alone until the user
+    /// assigns them again
+    pub cleared: Vec<bool>,
     pub previous_session: Option<String>,
     pub metadata: HashMap<String, SessionMetadata>,
+    pub undo_journal: Vec<HotbarEdit>,
+    pub redo_journal: Vec<HotbarEdit>,
+    /// Seconds each session had a client attached, keyed by session and then
+    /// by day (days since the unix epoch)
+    pub focus_time: HashMap<String, HashMap<u64, u64>>,
 }
 
 impl Default for HotbarData {
     fn default() -> Self {
         Self {
             favorites: vec![None; 5],
+            auto_assigned: vec![false; 5],
+            locked: vec![false; 5],
+            cleared: vec![false; 5],
             previous_session: None,
             metadata: HashMap::new(),
+            undo_journal: Vec::new(),
+            redo_journal: Vec::new(),
+            focus_time: HashMap::new(),
         }
     }
 }
 
 impl HotbarData {
-    pub fn save_to_file(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let bytes = rkyv::to_bytes::<_, 256>(self)?;
-        let path = get_hotbar_file_path();
-        std::fs::write(&path, bytes.as_ref())?;
-        Ok(())
+    pub fn load_from_path(path: &Path) -> Result<Self, HotbarError> {
+        let bytes = std::fs::read(path)?;
+
+        let (version, archive) = match bytes.strip_prefix(MAGIC) {
+            Some(rest) => {
+                let version = rest
+                    .get(..HEADER_LEN - MAGIC.len())
+                    .and_then(|version| version.try_into().ok())
+                    .map(u32::from_le_bytes)
+                    .ok_or_else(|| HotbarError::CorruptArchive("truncated header".to_string()))?;
+                (version, &bytes[HEADER_LEN..])
+            }
+            // Only the first releases saved without the header, always in
+            // the layout of `HotbarDataV0`
+            None => (0, &bytes[..]),
+        };
+
+        // The archive is validated rather than trusted, so a damaged file is
+        // rejected instead of being misread. Validation needs the buffer to be
+        // aligned.
+        let mut aligned = rkyv::AlignedVec::with_capacity(archive.len());
+        aligned.extend_from_slice(archive);
+        match version {
+            0 => {
+                let archived = rkyv::check_archived_root::<HotbarDataV0>(&aligned)
+                    .map_err(|e| HotbarError::CorruptArchive(e.to_string()))?;
+                let Ok(data): Result<HotbarDataV0, _> = archived.deserialize(&mut rkyv::Infallible);
+                Ok(data.into())
+            }
+            1 => {
+                let archived = rkyv::check_archived_root::<HotbarDataV1>(&aligned)
+                    .map_err(|e| HotbarError::CorruptArchive(e.to_string()))?;
+                let Ok(data): Result<HotbarDataV1, _> = archived.deserialize(&mut rkyv::Infallible);
+                Ok(data.into())
+            }
+            FORMAT_VERSION => {
+                let archived = rkyv::check_archived_root::<HotbarData>(&aligned)
+                    .map_err(|e| HotbarError::CorruptArchive(e.to_string()))?;
+                let Ok(data) = archived.deserialize(&mut rkyv::Infallible);
+                Ok(data)
+            }
+            found => Err(HotbarError::VersionMismatch {
+                found,
+                expected: FORMAT_VERSION,
+            }),
+        }
     }
 
-    pub fn load_from_file() -> Result<Self, Box<dyn std::error::Error>> {
-        let path = get_hotbar_file_path();
-        let bytes = std::fs::read(&path)?;
+    /// Lists inconsistencies the plugin would trip over, e.g. in a data file
+    /// written by hand or by another tool. Empty when the data is sound.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (field, len) in [
+            ("favorites", self.favorites.len()),
+            ("auto_assigned", self.auto_assigned.len()),
+            ("locked", self.locked.len()),
+            ("cleared", self.cleared.len()),
+        ] {
+            if len != 5 {
+                problems.push(format!("{} has {} slots instead of 5", field, len));
+            }
+        }
+
+        for (slot, session) in self.favorites.iter().enumerate() {
+            match session {
+                Some(session) if session.trim().is_empty() => {
+                    problems.push(format!("slot {} holds an empty session name", slot + 1))
+                }
+                None if self.is_auto_assigned(slot) => problems.push(format!(
+                    "slot {} is empty but marked auto-assigned",
+                    slot + 1
+                )),
+                _ => {}
+            }
+        }
 
-        // SAFETY: We trust the data because:
-        // 1. We control both serialization and deserialization
-        // 2. The file is written atomically by this plugin only
-        // 3. The data format is stable and managed by rkyv
-        let archived = unsafe { rkyv::archived_root::<HotbarData>(&bytes) };
+        let journal_slots = self
+            .undo_journal
+            .iter()
+            .chain(&self.redo_journal)
+            .flat_map(|edit| &edit.changes)
+            .map(|change| change.slot);
+        for slot in journal_slots.filter(|slot| *slot >= 5) {
+            problems.push(format!("undo history refers to slot {}", slot + 1));
+        }
 
-        let data: HotbarData = archived.deserialize(&mut rkyv::Infallible)?;
-        Ok(data)
+        problems
     }
 
     pub fn get_session_at_slot(&self, slot: usize) -> Option<&String> {
@@ -63,26 +471,434 @@ impl HotbarData {
 
     pub fn set_session_at_slot(&mut self, slot: usize, session_name: Option<String>) {
         if slot < 5 {
+            self.cleared[slot] = session_name.is_none();
             self.favorites[slot] = session_name;
+            self.auto_assigned[slot] = false;
+        }
+    }
+
+    /// Applies a user edit to the slots and records it in the undo journal.
+    /// Slots whose contents don't change are left out of the record.
+    pub fn edit_slots(&mut self, edits: &[(usize, Option<String>)]) -> bool {
+        let changes: Vec<SlotChange> = edits
+            .iter()
+            .filter(|(slot, _)| *slot < 5)
+            .filter(|(slot, after)| self.favorites[*slot] != *after)
+            .map(|(slot, after)| SlotChange {
+                slot: *slot,
+                before: self.favorites[*slot].clone(),
+                after: after.clone(),
+            })
+            .collect();
+        if changes.is_empty() {
+            return false;
+        }
+
+        for change in &changes {
+            self.set_session_at_slot(change.slot, change.after.clone());
+        }
+        self.undo_journal.push(HotbarEdit { changes });
+        if self.undo_journal.len() > MAX_JOURNAL_LEN {
+            self.undo_journal.remove(0);
+        }
+        self.redo_journal.clear();
+        true
+    }
+
+    pub fn undo(&mut self) -> Option<&HotbarEdit> {
+        let edit = self.undo_journal.pop()?;
+        for change in edit.changes.iter().rev() {
+            self.set_session_at_slot(change.slot, change.before.clone());
+        }
+        self.redo_journal.push(edit);
+        self.redo_journal.last()
+    }
+
+    pub fn redo(&mut self) -> Option<&HotbarEdit> {
+        let edit = self.redo_journal.pop()?;
+        for change in &edit.changes {
+            self.set_session_at_slot(change.slot, change.after.clone());
+        }
+        self.undo_journal.push(edit);
+        self.undo_journal.last()
+    }
+
+    pub fn set_auto_session_at_slot(&mut self, slot: usize, session_name: Option<String>) {
+        if slot < 5 {
+            self.auto_assigned[slot] = session_name.is_some();
+            self.favorites[slot] = session_name;
+        }
+    }
+
+    pub fn is_auto_assigned(&self, slot: usize) -> bool {
+        self.auto_assigned.get(slot).copied().unwrap_or(false)
+    }
+
+    pub fn is_cleared(&self, slot: usize) -> bool {
+        self.cleared.get(slot).copied().unwrap_or(false)
+    }
+
+    pub fn is_locked(&self, slot: usize) -> bool {
+        self.locked.get(slot).copied().unwrap_or(false)
+    }
+
+    /// The locked slots whose contents `edits` would change. Every path that
+    /// edits the slots asks for confirmation, or is forced, before changing
+    /// them.
+    pub fn locked_changes(&self, edits: &[(usize, Option<String>)]) -> Vec<usize> {
+        edits
+            .iter()
+            .filter(|(slot, after)| {
+                self.is_locked(*slot) && self.favorites.get(*slot) != Some(after)
+            })
+            .map(|(slot, _)| *slot)
+            .collect()
+    }
+
+    pub fn toggle_lock(&mut self, slot: usize) {
+        if slot < 5 {
+            self.locked[slot] = !self.locked[slot];
         }
     }
 
     pub fn update_metadata(&mut self, session_name: &str, tab_count: usize) {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        self.metadata.insert(
-            session_name.to_string(),
-            SessionMetadata {
-                last_accessed: now,
-                tab_count: tab_count as u64,
-            },
-        );
+        let metadata = self.metadata.entry(session_name.to_string()).or_default();
+        metadata.last_accessed = unix_now();
+        metadata.tab_count = tab_count as u64;
+        metadata.access_count += 1;
+    }
+
+    pub fn set_note(&mut self, session_name: &str, note: &str) {
+        let metadata = self.metadata.entry(session_name.to_string()).or_default();
+        metadata.note = note.trim().to_string();
+    }
+
+    /// Replaces the session's tags with the comma or space separated ones in
+    /// `tags`, dropping any leading `#`.
+    pub fn set_tags(&mut self, session_name: &str, tags: &str) {
+        let mut parsed: Vec<String> = Vec::new();
+        for tag in tags
+            .split([',', ' '])
+            .map(|tag| tag.trim().trim_start_matches('#'))
+            .filter(|tag| !tag.is_empty())
+        {
+            if !parsed.iter().any(|parsed| parsed == tag) {
+                parsed.push(tag.to_string());
+            }
+        }
+
+        let metadata = self.metadata.entry(session_name.to_string()).or_default();
+        metadata.tags = parsed;
     }
 
     pub fn get_metadata(&self, session_name: &str) -> Option<&SessionMetadata> {
         self.metadata.get(session_name)
     }
+
+    /// Adds the time between `start` and `end` (unix seconds) to the session's
+    /// focus time, split at midnight (UTC) between the days it spans.
+    pub fn add_focus_time(&mut self, session_name: &str, start: u64, end: u64) {
+        let days = self.focus_time.entry(session_name.to_string()).or_default();
+        let mut start = start;
+        while start < end {
+            let day = dates::day_of(start);
+            let day_end = ((day + 1) * dates::SECONDS_PER_DAY).min(end);
+            *days.entry(day).or_default() += day_end - start;
+            start = day_end;
+        }
+    }
+
+    /// Total focus time of a session between two days, inclusive.
+    pub fn focus_seconds(&self, session_name: &str, from_day: u64, to_day: u64) -> u64 {
+        self.focus_time.get(session_name).map_or(0, |days| {
+            days.iter()
+                .filter(|(day, _)| (from_day..=to_day).contains(*day))
+                .map(|(_, seconds)| seconds)
+                .sum()
+        })
+    }
+}
+
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+pub fn unix_now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_and_loads_archive() {
+        let data_file = DataFile::temp("roundtrip");
+        let mut data = HotbarData::default();
+        data.edit_slots(&[(1, Some("api".to_string()))]);
+        data.set_tags("api", "work");
+        data_file.save(&data).unwrap();
+
+        let loaded = data_file.load().unwrap();
+        assert_eq!(
+            loaded.get_session_at_slot(1).map(String::as_str),
+            Some("api")
+        );
+        assert_eq!(loaded.get_metadata("api").unwrap().tags, vec!["work"]);
+        assert_eq!(loaded.undo_journal.len(), 1);
+    }
+
+    #[test]
+    fn backs_up_replaced_slots() {
+        let data_file = DataFile::temp("backups");
+        let mut data = HotbarData::default();
+        data.edit_slots(&[(0, Some("api".to_string()))]);
+        data_file.save(&data).unwrap();
+
+        // Metadata-only saves leave no backup
+        data.set_note("api", "main service");
+        data_file.save(&data).unwrap();
+        assert!(data_file.list_backups().is_empty());
+
+        data.edit_slots(&[(0, Some("web".to_string()))]);
+        data_file.save(&data).unwrap();
+        let backups = data_file.list_backups();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(
+            backups[0].data.get_session_at_slot(0).map(String::as_str),
+            Some("api")
+        );
+    }
+
+    #[test]
+    fn keeps_backups_taken_within_the_same_second() {
+        let data_file = DataFile::temp("same_second");
+        let mut data = HotbarData::default();
+        for session in ["api", "web", "docs"] {
+            data.edit_slots(&[(0, Some(session.to_string()))]);
+            data_file.save_at(&data, 1_760_000_000).unwrap();
+        }
+
+        let backups = data_file.list_backups();
+        let slots: Vec<Option<&str>> = backups
+            .iter()
+            .map(|backup| backup.data.get_session_at_slot(0).map(String::as_str))
+            .collect();
+        assert_eq!(slots, vec![Some("web"), Some("api")]);
+    }
+
+    #[test]
+    fn rejects_other_versions_and_damaged_files() {
+        let data_file = DataFile::temp("versions");
+        data_file.save(&HotbarData::default()).unwrap();
+        let mut bytes = std::fs::read(data_file.path()).unwrap();
+
+        bytes[4..8].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        std::fs::write(data_file.path(), &bytes).unwrap();
+        assert!(matches!(
+            data_file.load(),
+            Err(HotbarError::VersionMismatch { found, .. }) if found == FORMAT_VERSION + 1
+        ));
+
+        std::fs::write(data_file.path(), b"not an archive").unwrap();
+        assert!(matches!(
+            data_file.load(),
+            Err(HotbarError::CorruptArchive(_))
+        ));
+
+        std::fs::remove_file(data_file.path()).unwrap();
+        assert!(matches!(data_file.load(), Err(HotbarError::Io(_))));
+    }
+
+    #[test]
+    fn migrates_version_0_files() {
+        let data_file = DataFile::temp("version_0");
+        let old = HotbarDataV0 {
+            favorites: vec![Some("api".to_string()), None, None, None, None],
+            previous_session: Some("web".to_string()),
+            metadata: HashMap::from([(
+                "api".to_string(),
+                SessionMetadataV0 {
+                    last_accessed: 1_760_000_000,
+                    tab_count: 3,
+                },
+            )]),
+        };
+        std::fs::write(data_file.path(), rkyv::to_bytes::<_, 256>(&old).unwrap()).unwrap();
+
+        let data = data_file.load().unwrap();
+        assert!(data.problems().is_empty());
+        assert_eq!(data.get_session_at_slot(0).map(String::as_str), Some("api"));
+        assert_eq!(data.previous_session.as_deref(), Some("web"));
+        assert_eq!(data.get_metadata("api").unwrap().tab_count, 3);
+    }
+
+    #[test]
+    fn migrates_version_1_files() {
+        let data_file = DataFile::temp("version_1");
+        let old = HotbarDataV1 {
+            favorites: vec![Some("api".to_string()), None, None, None, None],
+            auto_assigned: vec![true, false, false, false, false],
+            locked: vec![false, false, true, false, false],
+            previous_session: None,
+            metadata: HashMap::new(),
+            undo_journal: Vec::new(),
+            redo_journal: Vec::new(),
+            focus_time: HashMap::from([("api".to_string(), HashMap::from([(20_000, 60)]))]),
+        };
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&rkyv::to_bytes::<_, 256>(&old).unwrap());
+        std::fs::write(data_file.path(), bytes).unwrap();
+
+        let data = data_file.load().unwrap();
+        assert!(data.problems().is_empty());
+        assert!(data.is_auto_assigned(0) && data.is_locked(2));
+        assert!((0..5).all(|slot| !data.is_cleared(slot)));
+        assert_eq!(data.focus_seconds("api", 20_000, 20_000), 60);
+    }
+
+    // Written by the first release's `save_to_file`
+    #[test]
+    fn loads_files_from_the_first_release() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/fixtures/hotbar-v0.rkyv");
+        let data = HotbarData::load_from_path(&path).unwrap();
+
+        assert_eq!(
+            data.favorites,
+            vec![
+                Some("api".to_string()),
+                None,
+                None,
+                Some("docs".to_string()),
+                None
+            ]
+        );
+        assert_eq!(data.previous_session.as_deref(), Some("docs"));
+        let api = data.get_metadata("api").unwrap();
+        assert_eq!((api.last_accessed, api.tab_count), (1_760_000_000, 4));
+        assert!(data.problems().is_empty());
+    }
+
+    #[test]
+    fn moves_unreadable_files_aside_before_saving() {
+        let data_file = DataFile::temp("unreadable");
+        std::fs::write(data_file.path(), b"not an archive").unwrap();
+        data_file.save(&HotbarData::default()).unwrap();
+
+        let dir = data_file.path().parent().unwrap();
+        let corrupt: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".corrupt-"))
+            .collect();
+        assert_eq!(corrupt.len(), 1);
+        assert_eq!(std::fs::read(corrupt[0].path()).unwrap(), b"not an archive");
+        assert!(data_file.load().is_ok());
+    }
+
+    #[test]
+    fn finds_locked_slots_an_edit_changes() {
+        let mut data = HotbarData::default();
+        data.edit_slots(&[(0, Some("api".to_string())), (1, Some("web".to_string()))]);
+        data.toggle_lock(0);
+        data.toggle_lock(2);
+
+        let undo = data.undo_journal.last().unwrap().reverted();
+        assert_eq!(data.locked_changes(&undo), vec![0]);
+        // Locked but left as it is
+        assert!(
+            data.locked_changes(&[(0, Some("api".to_string())), (2, None)])
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn leaves_files_of_other_versions_alone() {
+        let data_file = DataFile::temp("newer_version");
+        data_file.save(&HotbarData::default()).unwrap();
+        let mut bytes = std::fs::read(data_file.path()).unwrap();
+        bytes[4..8].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        std::fs::write(data_file.path(), &bytes).unwrap();
+
+        assert!(matches!(
+            data_file.save(&HotbarData::default()),
+            Err(HotbarError::VersionMismatch { .. })
+        ));
+        assert_eq!(std::fs::read(data_file.path()).unwrap(), bytes);
+        let dir = std::fs::read_dir(data_file.path().parent().unwrap()).unwrap();
+        assert_eq!(dir.count(), 1);
+    }
+
+    #[test]
+    fn parses_tags_without_duplicates() {
+        let mut data = HotbarData::default();
+        data.set_tags("api", "#a b, a  c b");
+
+        assert_eq!(data.get_metadata("api").unwrap().tags, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn imports_partial_json_with_defaults() {
+        let data: HotbarData =
+            serde_json::from_str(r#"{"favorites": ["api", null, null, null, "web"]}"#).unwrap();
+
+        assert!(data.problems().is_empty());
+        assert_eq!(data.get_session_at_slot(4).map(String::as_str), Some("web"));
+        assert!(!data.is_locked(0));
+    }
+
+    #[test]
+    fn reports_inconsistent_data() {
+        let mut data = HotbarData::default();
+        data.favorites.pop();
+        data.auto_assigned[2] = true;
+        data.undo_journal.push(HotbarEdit {
+            changes: vec![SlotChange {
+                slot: 7,
+                before: None,
+                after: None,
+            }],
+        });
+
+        assert_eq!(
+            data.problems(),
+            vec![
+                "favorites has 4 slots instead of 5",
+                "slot 3 is empty but marked auto-assigned",
+                "undo history refers to slot 8",
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_focus_time_at_midnight() {
+        let day = dates::parse_day("2024-02-28").unwrap();
+        let midnight = (day + 1) * dates::SECONDS_PER_DAY;
+        let mut data = HotbarData::default();
+
+        // From 23:50 to 00:10 two days later, across the leap day
+        data.add_focus_time(
+            "api",
+            midnight - 600,
+            midnight + dates::SECONDS_PER_DAY + 600,
+        );
+        data.add_focus_time("api", midnight + 7_200, midnight + 7_200);
+
+        let days = &data.focus_time["api"];
+        assert_eq!(days.get(&day).copied(), Some(600));
+        assert_eq!(days.get(&(day + 1)).copied(), Some(dates::SECONDS_PER_DAY));
+        assert_eq!(days.get(&(day + 2)).copied(), Some(600));
+        assert_eq!(days.len(), 3);
+        assert_eq!(
+            data.focus_seconds("api", day, day + 1),
+            600 + dates::SECONDS_PER_DAY
+        );
+    }
 }
@@ -0,0 +1,217 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The number of terminal columns `text` takes, counting wide characters
+/// (CJK, most emoji) as two.
+pub fn text_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Cuts `text` down to `max_width` columns, ending it with `…` when it had
+/// to be shortened.
+pub fn ellipsize(text: &str, max_width: usize) -> String {
+    if text_width(text) <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+
+    let mut shortened = String::new();
+    let mut width = 0;
+    for ch in text.chars() {
+        let ch_width = ch.width().unwrap_or(0);
+        if width + ch_width > max_width - 1 {
+            break;
+        }
+        shortened.push(ch);
+        width += ch_width;
+    }
+    shortened.push('…');
+    shortened
+}
+
+/// Pads `text` with spaces to `width` columns, or ellipsizes it when longer.
+pub fn pad(text: &str, width: usize) -> String {
+    let text = ellipsize(text, width);
+    let padding = width - text_width(&text);
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cell {
+    Blank,
+    /// A character and the SGR parameters it is drawn with
+    Char(char, &'static str),
+    /// The second column taken by a wide character
+    WideTail,
+}
+
+/// A grid of terminal cells the UI is drawn into before being written out in
+/// one go. Rows and columns are 1-based like cursor positions, and anything
+/// drawn outside the grid is clipped.
+pub struct Screen {
+    rows: usize,
+    cols: usize,
+    cells: Vec<Cell>,
+}
+
+impl Screen {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            cells: vec![Cell::Blank; rows * cols],
+        }
+    }
+
+    /// Draws `text` starting at `row`/`col` and returns the column following
+    /// it, so more text can be appended on the same line.
+    pub fn print(&mut self, row: usize, col: usize, text: &str, style: &'static str) -> usize {
+        let mut col = col;
+        for ch in text.chars() {
+            // Control and combining characters have no cell of their own
+            let width = match ch.width() {
+                Some(width) if width > 0 => width,
+                _ => continue,
+            };
+            if (1..=self.rows).contains(&row) && col >= 1 && col + width - 1 <= self.cols {
+                let index = self.index(row, col);
+                for offset in 0..width {
+                    self.clear(row, col + offset);
+                }
+                self.cells[index] = Cell::Char(ch, style);
+                if width == 2 {
+                    self.cells[index + 1] = Cell::WideTail;
+                }
+            }
+            col += width;
+        }
+        col
+    }
+
+    fn index(&self, row: usize, col: usize) -> usize {
+        (row - 1) * self.cols + (col - 1)
+    }
+
+    // Overwriting either half of a wide character blanks the other half
+    fn clear(&mut self, row: usize, col: usize) {
+        let index = self.index(row, col);
+        if self.cells[index] == Cell::WideTail {
+            self.cells[index - 1] = Cell::Blank;
+        } else if col < self.cols && self.cells[index + 1] == Cell::WideTail {
+            self.cells[index + 1] = Cell::Blank;
+        }
+        self.cells[index] = Cell::Blank;
+    }
+
+    fn row_cells(&self, row: usize) -> &[Cell] {
+        &self.cells[(row - 1) * self.cols..row * self.cols]
+    }
+
+    /// The escape sequences that clear the pane and draw the grid.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::from("\u{001b}[2J");
+        for row in 1..=self.rows {
+            let cells = self.row_cells(row);
+            let Some(last) = cells.iter().rposition(|cell| *cell != Cell::Blank) else {
+                continue;
+            };
+
+            out.push_str(&format!("\u{001b}[{};1H", row));
+            let mut current_style = "";
+            for cell in &cells[..=last] {
+                let (ch, style) = match cell {
+                    Cell::Blank => (' ', ""),
+                    Cell::Char(ch, style) => (*ch, *style),
+                    Cell::WideTail => continue,
+                };
+                if style != current_style {
+                    out.push_str("\u{001b}[0m");
+                    if !style.is_empty() {
+                        out.push_str(&format!("\u{001b}[{}m", style));
+                    }
+                    current_style = style;
+                }
+                out.push(ch);
+            }
+            if !current_style.is_empty() {
+                out.push_str("\u{001b}[0m");
+            }
+        }
+        out
+    }
+
+    /// The grid as plain text without styles, trailing blanks trimmed.
+    #[cfg(test)]
+    pub fn to_text(&self) -> String {
+        (1..=self.rows)
+            .map(|row| {
+                let line: String = self
+                    .row_cells(row)
+                    .iter()
+                    .filter_map(|cell| match cell {
+                        Cell::Blank => Some(' '),
+                        Cell::Char(ch, _) => Some(*ch),
+                        Cell::WideTail => None,
+                    })
+                    .collect();
+                line.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ellipsizes_by_display_width() {
+        assert_eq!(ellipsize("session", 7), "session");
+        assert_eq!(ellipsize("session", 5), "sess…");
+        assert_eq!(ellipsize("session", 0), "");
+        // A wide character that would straddle the limit is left out
+        assert_eq!(ellipsize("日本語のセッション", 6), "日本…");
+        assert_eq!(ellipsize("日本語のセッション", 7), "日本語…");
+        assert_eq!(text_width(&ellipsize("🚀rocket", 4)), 4);
+        assert_eq!(pad("日本", 6), "日本  ");
+    }
+
+    #[test]
+    fn clips_text_outside_the_grid() {
+        let mut screen = Screen::new(2, 5);
+        assert_eq!(screen.print(1, 3, "hello", ""), 8);
+        screen.print(3, 1, "gone", "");
+        screen.print(0, 1, "gone", "");
+
+        assert_eq!(screen.to_text(), "  hel\n");
+    }
+
+    #[test]
+    fn wide_characters_take_two_cells() {
+        let mut screen = Screen::new(1, 6);
+        assert_eq!(screen.print(1, 1, "a界b", ""), 5);
+        assert_eq!(screen.to_text(), "a界b");
+
+        // Overwriting half of the wide character blanks the other half
+        screen.print(1, 3, "x", "");
+        assert_eq!(screen.to_text(), "a xb");
+
+        // A wide character that doesn't fit in the last column is dropped
+        screen.print(1, 6, "界", "");
+        assert_eq!(screen.to_text(), "a xb");
+    }
+
+    #[test]
+    fn ansi_output_switches_styles_between_runs() {
+        let mut screen = Screen::new(2, 10);
+        let col = screen.print(2, 2, "ab", "1");
+        screen.print(2, col, "c", "");
+
+        assert_eq!(
+            screen.to_ansi(),
+            "\u{001b}[2J\u{001b}[2;1H \u{001b}[0m\u{001b}[1mab\u{001b}[0mc"
+        );
+    }
+}
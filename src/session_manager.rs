@@ -1,9 +1,30 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use zellij_tile::prelude::*;
 
-use crate::persistence::HotbarData;
+use crate::auto_assign::AutoAssignConfig;
+use crate::bindings::{self, BindingFormat};
+use crate::dates;
+use crate::discovery::{self, DiscoveryConfig, Project};
+use crate::error::{HotbarError, SwitchTarget};
+use crate::filter;
+use crate::geometry::ModalGeometry;
+use crate::grouping::{self, GroupBy, ListRow};
+use crate::hooks::{self, HookConfig, HookResult, HookStage, WaitingSwitch};
+use crate::host::Host;
+use crate::logging::{Log, LogLevel};
+use crate::notify::{Level, Notification, NotificationConfig};
+use crate::persistence::{self, Backup, DataFile, HotbarData};
+use crate::switcher::{self, Switcher};
 
 pub const PREFIX_KEY: &str = "hotbar_";
+pub const SELECT_ON_OPEN_KEY: &str = "select_on_open";
+const PERMISSIONS: [PermissionType; 4] = [
+    PermissionType::ChangeApplicationState,
+    PermissionType::ReadApplicationState,
+    PermissionType::RunCommands,
+    PermissionType::ReadCliPipes,
+];
+const FOCUS_FLUSH_INTERVAL_SECS: u64 = 60;
 
 #[derive(Default)]
 pub enum HotBarMode {
@@ -12,77 +33,518 @@ pub enum HotBarMode {
     Tab,
 }
 
+/// Which session the modal selects when it opens.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum OpenSelection {
+    #[default]
+    Current,
+    /// The most recently used session other than the current one
+    Previous,
+}
+
+impl OpenSelection {
+    pub fn from_config(value: &str) -> Self {
+        match value {
+            "previous" => OpenSelection::Previous,
+            _ => OpenSelection::Current,
+        }
+    }
+}
+
+/// The user's answer to the plugin's permission request.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum PermissionState {
+    /// Not answered yet; host calls are attempted anyway
+    #[default]
+    Pending,
+    Granted,
+    Denied,
+}
+
 #[derive(Default, PartialEq)]
 pub enum ModalMode {
     #[default]
     Normal,
     Move,
+    /// Waiting for `y` before changing a locked slot
+    Confirm(PendingChange),
+    /// Listing backups of the data file to restore one
+    RestoreBackup,
+    /// Showing focus time per session
+    Stats,
+    /// Typing a filter for the session list
+    Filter,
+    /// Editing the note of the named session
+    EditNote(String),
+    /// Editing the tags of the named session
+    EditTags(String),
+    /// Typing tags to add to every marked session
+    TagMarked,
+    /// Showing keybinding snippets for the plugin's commands
+    Bindings,
+    /// Showing the plugin's recent log messages
+    Log,
+    /// Cycling through recently used sessions, see `Switcher`
+    Switcher,
+}
+
+#[derive(PartialEq)]
+pub enum PendingChange {
+    Assign {
+        slot: usize,
+        session: String,
+    },
+    Remove {
+        slot: usize,
+    },
+    /// Marked sessions going into free slots, and those that didn't fit
+    AssignMarked {
+        assignments: Vec<(usize, String)>,
+        left_out: Vec<String>,
+    },
+    /// The slots holding marked sessions, with what they held
+    RemoveMarked(Vec<(usize, String)>),
+    TagMarked {
+        sessions: Vec<String>,
+        tags: String,
+    },
+    KillSessions(Vec<String>),
+    /// Undoing the last edit, which changes these locked slots
+    Undo(Vec<usize>),
+    /// Redoing the last undone edit, which changes these locked slots
+    Redo(Vec<usize>),
+    /// Putting back the slots of the backup taken at `timestamp`
+    RestoreBackup {
+        timestamp: u64,
+        edits: Vec<(usize, Option<String>)>,
+        locked: Vec<usize>,
+    },
 }
 
 #[derive(Default)]
 pub struct HotbarManager {
+    pub host: Box<dyn Host>,
+    pub data_file: DataFile,
     pub saved_sessions: HashMap<String, String>,
     pub hotbar_mode: HotBarMode,
     pub active_session: Option<String>,
     previous_session: Option<String>,
     pub hotbar_data: HotbarData,
     pub selected_index: usize,
+    /// The session last selected in this instance, kept selected while the
+    /// rows are rebuilt and brought back once a filter stops hiding it
+    selected_session: Option<String>,
+    pub select_on_open: OpenSelection,
+    /// Sessions marked for a bulk action, in the order they were marked
+    pub marked: Vec<String>,
     pub modal_mode: ModalMode,
     pub all_sessions: Vec<String>,
+    /// What the modal lists: `all_sessions`, possibly grouped under headers.
+    /// `selected_index` and `scroll_offset` index into it.
+    pub rows: Vec<ListRow>,
+    pub group_by: GroupBy,
+    pub group_separator: String,
+    collapsed_groups: HashSet<String>,
+    pub sessions: Vec<SessionInfo>,
+    pub discovery: DiscoveryConfig,
+    pub projects: Vec<Project>,
+    pub auto_assign: AutoAssignConfig,
+    pub hooks: HookConfig,
+    waiting_switch: Option<WaitingSwitch>,
+    pub backups: Vec<Backup>,
+    pub backup_index: usize,
+    /// When this instance's session last gained a client or had its focus time flushed
+    focus_started: Option<u64>,
+    focus_timer_due: u64,
     pub is_visible: bool,
     pub scroll_offset: usize,
+    /// Query narrowing down `all_sessions`, see `filter::matches`
+    pub filter: String,
+    /// Text being typed in the filter, note or tags prompt
+    pub input: String,
+    pub bindings_format: BindingFormat,
+    pub bindings_scroll: usize,
+    pub notifications: NotificationConfig,
+    pub notification: Option<Notification>,
+    /// The pane is only shown for `notification`, without the modal
+    pub showing_toast: bool,
+    pub log: Log,
+    pub log_scroll: usize,
+    /// The last problem reading the data file, reported once rather than on
+    /// every reload
+    load_error: Option<String>,
+    pub permissions: PermissionState,
+    pub switcher: Switcher,
+    pub geometry: ModalGeometry,
+    /// Rows and columns of the active tab, which the geometry is relative to
+    viewport: Option<(usize, usize)>,
 }
 
 impl HotbarManager {
     pub fn show_ui(&mut self) {
-        show_self(true);
+        self.show_pane();
         self.is_visible = true;
-        set_selectable(true);
-        // Reset scroll to show the selected item
+        self.showing_toast = false;
+        self.host.set_selectable(true);
+        self.select_on_open();
+        // Rendering scrolls the selection back into view
         self.scroll_offset = 0;
+        // Refresh discovered projects in the background while the session
+        // list is open, the only screen that shows them
+        if self.modal_mode == ModalMode::Normal {
+            discovery::start_scan(self.host.as_ref(), &self.discovery);
+        }
     }
 
     pub fn hide_ui(&mut self) {
-        hide_self();
+        self.host.hide_self();
         self.is_visible = false;
-        set_selectable(false);
+        self.showing_toast = false;
+        // Marks left behind would make the next bulk action a surprise
+        self.marked.clear();
+        // The switcher only lasts while it's shown
+        if self.modal_mode == ModalMode::Switcher {
+            self.modal_mode = ModalMode::Normal;
+        }
+        self.host.set_selectable(false);
     }
 
-    fn switch_to_session(&mut self, hotbar_key: &str) {
-        if let Some(session) = self.saved_sessions.get(hotbar_key) {
-            switch_session(Some(session));
-            switch_to_input_mode(&InputMode::Normal);
+    fn show_pane(&mut self) {
+        self.host.show_self();
+        self.apply_geometry();
+    }
 
-            println!("Switched to session: {}", session);
-        } else {
-            println!("No session found with key: {hotbar_key}");
+    // Until the first tab update there's nothing to size the pane against,
+    // so it keeps Zellij's geometry
+    fn apply_geometry(&self) {
+        if let Some((rows, cols)) = self.viewport
+            && self.geometry.is_configured()
+        {
+            self.host
+                .change_floating_pane_coordinates(self.geometry.pane_rect(rows, cols));
         }
     }
 
-    fn switch_to_tab(&mut self, hotbar_key: &str) {
-        let tab_idx = hotbar_key.trim_start_matches(PREFIX_KEY);
-        if let Ok(idx) = tab_idx.parse::<u32>() {
-            switch_tab_to(idx);
-            switch_to_input_mode(&InputMode::Normal);
+    /// Records the active tab's size, moving the pane along when it's shown.
+    pub fn set_viewport(&mut self, rows: usize, cols: usize) {
+        if self.viewport == Some((rows, cols)) {
+            return;
+        }
+        self.viewport = Some((rows, cols));
+        if self.is_visible {
+            self.apply_geometry();
+        }
+    }
 
-            println!("Switched to tab index: {idx}");
-        } else {
-            println!("Invalid tab index: {tab_idx} for key: {hotbar_key}");
+    /// Logs `message` and, unless the configured verbosity hides it, shows
+    /// it for a few seconds: on the modal's top border when it's open,
+    /// otherwise by briefly showing the pane with just the message.
+    pub fn notify(&mut self, level: Level, message: String) {
+        let log_level = match level {
+            Level::Info => LogLevel::Info,
+            Level::Error => LogLevel::Error,
+        };
+        self.log.log(log_level, message.clone());
+        if !self.notifications.shows(level) {
+            return;
+        }
+
+        let duration_secs = self.notifications.duration_secs;
+        self.notification = Some(Notification {
+            level,
+            message,
+            expires_at: persistence::unix_now() + duration_secs,
+        });
+        if !self.is_visible {
+            // Not selectable, so typing keeps going to the focused pane
+            self.show_pane();
+            self.host.set_selectable(false);
+            self.is_visible = true;
+            self.showing_toast = true;
+        }
+        self.host.set_timeout(duration_secs as f64);
+    }
+
+    /// Dismisses the notification once it has expired, hiding the pane if it
+    /// was only shown for it. Returns whether anything changed.
+    pub fn expire_notification(&mut self) -> bool {
+        if self
+            .notification
+            .as_ref()
+            .is_none_or(|notification| persistence::unix_now() < notification.expires_at)
+        {
+            return false;
+        }
+
+        self.notification = None;
+        if self.showing_toast {
+            self.hide_ui();
+        }
+        true
+    }
+
+    pub fn request_permissions(&mut self) {
+        self.permissions = PermissionState::Pending;
+        self.host.request_permission(&PERMISSIONS);
+    }
+
+    pub fn set_permission_status(&mut self, status: PermissionStatus) {
+        match status {
+            PermissionStatus::Granted => {
+                self.permissions = PermissionState::Granted;
+                self.log.info("Permissions granted");
+                // The scan started on load fails while permissions are still
+                // being asked for
+                discovery::start_scan(self.host.as_ref(), &self.discovery);
+            }
+            PermissionStatus::Denied => {
+                self.permissions = PermissionState::Denied;
+                self.report_error(&Self::switch_permission_error());
+            }
+        }
+    }
+
+    fn switch_permission_error() -> HotbarError {
+        HotbarError::PermissionDenied("ChangeApplicationState".to_string())
+    }
+
+    /// Fails when the permissions were denied, as the host then ignores
+    /// switches without telling.
+    fn check_permissions(&self) -> Result<(), HotbarError> {
+        match self.permissions {
+            PermissionState::Denied => Err(Self::switch_permission_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reports a failed action in the log and as a notification, telling the
+    /// user how to fix it where they can.
+    pub fn report_error(&mut self, error: &HotbarError) {
+        let message = match error {
+            HotbarError::Io(e) => format!(
+                "Hotbar data file {}: {}",
+                self.data_file.path().display(),
+                e
+            ),
+            HotbarError::CorruptArchive(_) => format!(
+                "Hotbar data file {} is {}, restore a backup with b",
+                self.data_file.path().display(),
+                error
+            ),
+            HotbarError::VersionMismatch { .. } => format!(
+                "Hotbar data file {} is {}, reload the plugin to pick up its version",
+                self.data_file.path().display(),
+                error
+            ),
+            HotbarError::PermissionDenied(_) => {
+                format!("{}, open the hotbar to ask for it again", error)
+            }
+            _ => error.to_string(),
+        };
+        self.notify(Level::Error, message);
+    }
+
+    /// Reports the error of an action nobody waits for, e.g. a timer's.
+    pub fn report(&mut self, result: Result<(), HotbarError>) {
+        if let Err(e) = result {
+            self.report_error(&e);
+        }
+    }
+
+    /// Reports the error of an action a pipe asked for: to `zellij pipe` on
+    /// the command line, otherwise like `report`.
+    pub fn report_to(&mut self, source: &PipeSource, result: Result<(), HotbarError>) {
+        match (result, source) {
+            (Ok(()), _) => {}
+            (Err(e), PipeSource::Cli(_)) => {
+                self.log.log(LogLevel::Error, e.to_string());
+                self.reply(source, &format!("{}\n", e));
+            }
+            (Err(e), _) => self.report_error(&e),
+        }
+    }
+
+    /// Answers a pipe: on the command line for `zellij pipe`, otherwise in
+    /// the log, as keybindings have nowhere to show output.
+    pub fn reply(&mut self, source: &PipeSource, output: &str) {
+        match source {
+            PipeSource::Cli(pipe_id) => {
+                self.host.cli_pipe_output(pipe_id, output);
+                self.host.unblock_cli_pipe_input(pipe_id);
+            }
+            _ => self.log.info(output),
         }
     }
 
-    pub fn switch_to(&mut self, key: &str) {
+    fn switch_to_session(&mut self, hotbar_key: &str) -> Result<(), HotbarError> {
+        self.check_permissions()?;
+        let Some(session) = self.saved_sessions.get(hotbar_key) else {
+            return Err(HotbarError::MissingSession(SwitchTarget::Key(
+                hotbar_key.to_string(),
+            )));
+        };
+        self.host.switch_session(session);
+        self.host.switch_to_input_mode(InputMode::Normal);
+
+        self.log.info(format!("Switched to session: {}", session));
+        Ok(())
+    }
+
+    fn switch_to_tab(&mut self, hotbar_key: &str) -> Result<(), HotbarError> {
+        self.check_permissions()?;
+        let tab_idx = hotbar_key.trim_start_matches(PREFIX_KEY);
+        let idx = tab_idx
+            .parse::<u32>()
+            .map_err(|_| HotbarError::InvalidSlot(tab_idx.to_string()))?;
+        self.host.switch_tab_to(idx);
+        self.host.switch_to_input_mode(InputMode::Normal);
+
+        self.log.info(format!("Switched to tab index: {idx}"));
+        Ok(())
+    }
+
+    pub fn switch_to(&mut self, key: &str) -> Result<(), HotbarError> {
         match self.hotbar_mode {
             HotBarMode::Session => self.switch_to_session(key),
             HotBarMode::Tab => self.switch_to_tab(key),
         }
     }
 
-    pub fn switch_to_previous_session(&mut self) {
-        if let Some(promote_session) = self.previous_session.clone() {
-            // Don't call close_focus() - plugin runs headless
-            switch_session(Some(&promote_session));
-            switch_to_input_mode(&InputMode::Normal);
+    /// Switches to a running session, or creates a session for a discovered
+    /// project that has not been started yet, using the project as its cwd.
+    pub fn open_session(&self, session_name: &str) {
+        match self.project_for(session_name) {
+            Some(project) if !self.is_running(session_name) => {
+                self.host
+                    .switch_session_with_cwd(session_name, project.path.clone());
+            }
+            _ => self.host.switch_session(session_name),
+        }
+    }
+
+    pub fn switch_to_previous_session(&mut self) -> Result<(), HotbarError> {
+        self.check_permissions()?;
+        let promote_session = self
+            .previous_session
+            .clone()
+            .ok_or(HotbarError::MissingSession(SwitchTarget::Previous))?;
+        self.switch_with_hooks(&promote_session, None);
+        Ok(())
+    }
+
+    // When a before-switch hook is configured the switch waits for it to
+    // finish, see `handle_hook_result`, or for its timeout, see
+    // `expire_waiting_switch`
+    fn switch_with_hooks(&mut self, session_name: &str, slot: Option<usize>) {
+        let Some(command) = self.hooks.command_for(HookStage::BeforeSwitch, slot) else {
+            self.complete_switch(session_name, slot);
+            return;
+        };
+
+        hooks::run_hook(
+            self.host.as_ref(),
+            &mut self.log,
+            HookStage::BeforeSwitch,
+            command,
+            self.active_session.as_deref(),
+            session_name,
+            slot,
+        );
+        let timeout_ms = self.hooks.before_switch_timeout_ms;
+        self.waiting_switch = Some(WaitingSwitch {
+            target: session_name.to_string(),
+            slot,
+            deadline_ms: persistence::unix_now_millis() + timeout_ms,
+        });
+        self.host.set_timeout(timeout_ms as f64 / 1000.0);
+    }
+
+    /// Switches anyway once a before-switch hook has kept the switch waiting
+    /// past its timeout, e.g. because it hangs or `RunCommands` was denied.
+    /// Returns whether it switched.
+    pub fn expire_waiting_switch(&mut self) -> bool {
+        if self
+            .waiting_switch
+            .as_ref()
+            .is_none_or(|waiting| persistence::unix_now_millis() < waiting.deadline_ms)
+        {
+            return false;
+        }
+
+        let Some(waiting) = self.waiting_switch.take() else {
+            return false;
+        };
+        self.notify(
+            Level::Error,
+            format!(
+                "before_switch hook for '{}' didn't finish within {} ms, switching anyway",
+                waiting.target, self.hooks.before_switch_timeout_ms
+            ),
+        );
+        self.complete_switch(&waiting.target, waiting.slot);
+        true
+    }
+
+    fn complete_switch(&mut self, session_name: &str, slot: Option<usize>) {
+        // Don't call close_focus() - plugin runs headless
+        self.open_session(session_name);
+        self.host.switch_to_input_mode(InputMode::Normal);
+        self.log
+            .info(format!("Switched to session: {}", session_name));
+
+        if let Some(command) = self.hooks.command_for(HookStage::AfterSwitch, slot) {
+            hooks::run_hook(
+                self.host.as_ref(),
+                &mut self.log,
+                HookStage::AfterSwitch,
+                command,
+                self.active_session.as_deref(),
+                session_name,
+                slot,
+            );
+        }
+    }
+
+    /// Completes the switch waiting for a before-switch hook that succeeded,
+    /// and cancels it when the hook failed, so a hook can veto a switch. Once
+    /// the switch has stopped waiting, or waits for another hook, a late
+    /// result is only reported.
+    pub fn handle_hook_result(&mut self, exit_code: Option<i32>, stderr: &[u8], hook: HookResult) {
+        let succeeded = exit_code == Some(0);
+        let decides_switch = hook.stage == HookStage::BeforeSwitch
+            && self
+                .waiting_switch
+                .as_ref()
+                .is_some_and(|waiting| waiting.target == hook.target);
+
+        if succeeded {
+            self.log.info(format!(
+                "{:?} hook for '{}' succeeded",
+                hook.stage, hook.target
+            ));
+        } else {
+            self.notify(
+                Level::Error,
+                format!(
+                    "{:?} hook for '{}' failed with exit status {:?}{}: {}",
+                    hook.stage,
+                    hook.target,
+                    exit_code,
+                    if decides_switch {
+                        ", not switching"
+                    } else {
+                        ""
+                    },
+                    String::from_utf8_lossy(stderr).trim()
+                ),
+            );
+        }
+
+        if decides_switch {
+            self.waiting_switch = None;
+            if succeeded {
+                self.complete_switch(&hook.target, hook.slot);
+            }
         }
     }
 
@@ -98,76 +560,581 @@ impl HotbarManager {
             return false;
         }
 
+        self.record_session_access(session_name);
+
         self.previous_session = self.active_session.clone();
         self.active_session = Some(session_name.to_string());
 
         self.hotbar_data.previous_session = self.previous_session.clone();
 
-        eprintln!(
+        self.log.info(format!(
             "Active session changed to: {:?}, previous session: {:?}",
             self.active_session, self.previous_session
-        );
+        ));
 
         true
     }
 
+    fn record_session_access(&mut self, session_name: &str) {
+        // Reload first so assignments made by other plugin instances aren't overwritten
+        self.load_hotbar_data();
+        let tab_count = self.session_info(session_name).map_or(0, |s| s.tabs.len());
+        self.hotbar_data.update_metadata(session_name, tab_count);
+        let result = self.save_hotbar_data();
+        self.report(result);
+    }
+
+    /// Fills empty slots according to the configured auto-assign rules.
+    pub fn apply_auto_assign(&mut self) -> Result<(), HotbarError> {
+        if !self.auto_assign.is_enabled() {
+            return Ok(());
+        }
+
+        self.load_hotbar_data();
+        let sessions: Vec<String> = self.sessions.iter().map(|s| s.name.clone()).collect();
+        if self.auto_assign.apply(
+            &mut self.hotbar_data,
+            &sessions,
+            &self.projects,
+            persistence::unix_now(),
+        ) {
+            self.save_hotbar_data()?;
+            self.log.info(format!(
+                "Auto-assigned slots: {:?}",
+                self.hotbar_data.favorites
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reloads the data file, falling back to an empty hotbar when it's
+    /// missing or unreadable.
     pub fn load_hotbar_data(&mut self) {
-        match HotbarData::load_from_file() {
+        match self.data_file.load() {
             Ok(data) => {
                 self.hotbar_data = data;
                 self.previous_session = self.hotbar_data.previous_session.clone();
-                eprintln!("Loaded hotbar data: {:?}", self.hotbar_data);
+                self.load_error = None;
+                self.log
+                    .debug(format!("Loaded hotbar data: {:?}", self.hotbar_data));
+            }
+            // Nothing saved yet
+            Err(HotbarError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.hotbar_data = HotbarData::default();
             }
             Err(e) => {
-                eprintln!("Failed to load hotbar data: {}, using defaults", e);
+                self.log
+                    .warn(format!("Failed to load hotbar data: {}, using defaults", e));
                 self.hotbar_data = HotbarData::default();
+                if self.load_error.as_deref() != Some(e.to_string().as_str()) {
+                    self.load_error = Some(e.to_string());
+                    self.report_error(&e);
+                }
             }
         }
     }
 
-    pub fn save_hotbar_data(&self) {
-        if let Err(e) = self.hotbar_data.save_to_file() {
-            eprintln!("Failed to save hotbar data: {}", e);
+    pub fn save_hotbar_data(&mut self) -> Result<(), HotbarError> {
+        self.data_file.save(&self.hotbar_data)
+    }
+
+    pub fn remove_from_slot(&mut self, slot: usize) -> Result<(), HotbarError> {
+        if slot >= 5 {
+            return Err(HotbarError::InvalidSlot((slot + 1).to_string()));
         }
+        self.hotbar_data.edit_slots(&[(slot, None)]);
+        self.save_hotbar_data()?;
+        self.notify(
+            Level::Info,
+            format!("Removed session from slot {}", slot + 1),
+        );
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> Result<(), HotbarError> {
+        self.undo_confirmed(&[])
+    }
+
+    pub fn redo(&mut self) -> Result<(), HotbarError> {
+        self.redo_confirmed(&[])
     }
 
-    pub fn remove_from_slot(&mut self, slot: usize) {
-        if slot < 5 {
-            self.hotbar_data.set_session_at_slot(slot, None);
-            self.save_hotbar_data();
-            eprintln!("Removed session from slot {}", slot + 1);
+    // Asks first when the edit changes locked slots beyond the `confirmed` ones
+    fn undo_confirmed(&mut self, confirmed: &[usize]) -> Result<(), HotbarError> {
+        let locked = self
+            .hotbar_data
+            .undo_journal
+            .last()
+            .map(|edit| self.hotbar_data.locked_changes(&edit.reverted()))
+            .unwrap_or_default();
+        if locked.iter().any(|slot| !confirmed.contains(slot)) {
+            self.request_confirmation(PendingChange::Undo(locked));
+            return Ok(());
+        }
+
+        match self.hotbar_data.undo() {
+            Some(edit) => {
+                self.log.debug(format!("Undid hotbar edit: {:?}", edit));
+                self.save_hotbar_data()?;
+                self.notify(Level::Info, "Undid the last hotbar edit".to_string());
+            }
+            None => self.notify(Level::Info, "Nothing to undo".to_string()),
         }
+        Ok(())
     }
 
-    pub fn switch_to_slot(&mut self, slot: usize) {
-        if let Some(session_name) = self.hotbar_data.get_session_at_slot(slot) {
-            if self.active_session.as_deref() == Some(session_name.as_str()) {
-                eprintln!("Already in session: {}", session_name);
-                return;
+    fn redo_confirmed(&mut self, confirmed: &[usize]) -> Result<(), HotbarError> {
+        let locked = self
+            .hotbar_data
+            .redo_journal
+            .last()
+            .map(|edit| self.hotbar_data.locked_changes(&edit.applied()))
+            .unwrap_or_default();
+        if locked.iter().any(|slot| !confirmed.contains(slot)) {
+            self.request_confirmation(PendingChange::Redo(locked));
+            return Ok(());
+        }
+
+        match self.hotbar_data.redo() {
+            Some(edit) => {
+                self.log.debug(format!("Redid hotbar edit: {:?}", edit));
+                self.save_hotbar_data()?;
+                self.notify(Level::Info, "Redid the last hotbar edit".to_string());
             }
-            // Don't call close_focus() - plugin runs headless
-            switch_session(Some(session_name));
-            switch_to_input_mode(&InputMode::Normal);
-            eprintln!("Switched to session: {}", session_name);
+            None => self.notify(Level::Info, "Nothing to redo".to_string()),
+        }
+        Ok(())
+    }
+
+    pub fn open_backups(&mut self) {
+        self.backups = self.data_file.list_backups();
+        for (path, e) in self.data_file.unreadable_backups() {
+            self.log.warn(format!(
+                "Skipping unreadable backup {}: {}",
+                path.display(),
+                e
+            ));
+        }
+        self.backup_index = 0;
+        self.modal_mode = ModalMode::RestoreBackup;
+    }
+
+    pub fn close_backups(&mut self) {
+        self.backups.clear();
+        self.modal_mode = ModalMode::Normal;
+    }
+
+    pub fn navigate_backups(&mut self, direction: i32) {
+        let max_index = self.backups.len().saturating_sub(1);
+        if direction > 0 {
+            self.backup_index = (self.backup_index + 1).min(max_index);
+        } else if direction < 0 {
+            self.backup_index = self.backup_index.saturating_sub(1);
+        }
+    }
+
+    pub fn get_selected_backup(&self) -> Option<&Backup> {
+        self.backups.get(self.backup_index)
+    }
+
+    /// Restores the slots of a backup (0 is the newest). The restore is
+    /// recorded as a single edit, so it can be undone.
+    pub fn restore_backup(&mut self, index: usize) -> Result<(), HotbarError> {
+        let backups = self.data_file.list_backups();
+        let backup = backups
+            .get(index)
+            .ok_or(HotbarError::MissingBackup(index))?;
+        let edits: Vec<(usize, Option<String>)> = (0..5)
+            .map(|slot| (slot, backup.data.get_session_at_slot(slot).cloned()))
+            .collect();
+        self.restore_slots(backup.timestamp, edits, &[])
+    }
+
+    // Asks first when the restore changes locked slots beyond the `confirmed` ones
+    fn restore_slots(
+        &mut self,
+        timestamp: u64,
+        edits: Vec<(usize, Option<String>)>,
+        confirmed: &[usize],
+    ) -> Result<(), HotbarError> {
+        self.load_hotbar_data();
+        let locked = self.hotbar_data.locked_changes(&edits);
+        if locked.iter().any(|slot| !confirmed.contains(slot)) {
+            self.request_confirmation(PendingChange::RestoreBackup {
+                timestamp,
+                edits,
+                locked,
+            });
+            return Ok(());
+        }
+
+        self.hotbar_data.edit_slots(&edits);
+        self.save_hotbar_data()?;
+        self.notify(
+            Level::Info,
+            format!(
+                "Restored backup from {}",
+                dates::format_timestamp(timestamp)
+            ),
+        );
+        Ok(())
+    }
+
+    pub fn open_bindings(&mut self) {
+        self.bindings_scroll = 0;
+        self.modal_mode = ModalMode::Bindings;
+    }
+
+    pub fn cycle_bindings_format(&mut self, direction: i32) {
+        self.bindings_format = if direction > 0 {
+            self.bindings_format.next()
+        } else {
+            self.bindings_format.previous()
+        };
+        self.bindings_scroll = 0;
+    }
+
+    // Rendering clamps the scroll further so the last page stays full
+    pub fn scroll_bindings(&mut self, direction: i32) {
+        if direction > 0 {
+            let lines = bindings::generate(self.bindings_format, bindings::DEFAULT_PLUGIN_ALIAS)
+                .lines()
+                .count();
+            self.bindings_scroll = (self.bindings_scroll + 1).min(lines.saturating_sub(1));
+        } else if direction < 0 {
+            self.bindings_scroll = self.bindings_scroll.saturating_sub(1);
+        }
+    }
+
+    /// Opens the switcher, or moves its selection on and postpones the switch.
+    pub fn trigger_switcher(&mut self) -> Result<(), HotbarError> {
+        self.check_permissions()?;
+        if self.modal_mode == ModalMode::Switcher && self.is_visible {
+            self.switcher.advance(1);
         } else {
-            eprintln!("No session saved in slot {}", slot + 1);
+            self.load_hotbar_data();
+            let running: Vec<String> = self.sessions.iter().map(|s| s.name.clone()).collect();
+            let sessions = switcher::recent_sessions(
+                &self.hotbar_data,
+                &running,
+                self.active_session.as_deref(),
+            );
+            if sessions.len() < 2 {
+                return Err(HotbarError::MissingSession(SwitchTarget::Previous));
+            }
+            self.switcher.open(sessions);
+            self.modal_mode = ModalMode::Switcher;
+            self.show_ui();
+        }
+        self.postpone_switch();
+        Ok(())
+    }
+
+    /// Moves the switcher's selection with the arrow keys.
+    pub fn move_switcher(&mut self, direction: i32) {
+        self.switcher.advance(direction);
+        self.postpone_switch();
+    }
+
+    fn postpone_switch(&mut self) {
+        let delay_secs = self.switcher.postpone(persistence::unix_now_millis());
+        self.host.set_timeout(delay_secs);
+    }
+
+    /// Switches to the switcher's selection once the triggers have stopped
+    /// for long enough. Returns whether the switcher closed.
+    pub fn commit_switcher_if_due(&mut self) -> bool {
+        if self.modal_mode != ModalMode::Switcher
+            || !self.switcher.is_due(persistence::unix_now_millis())
+        {
+            return false;
         }
+        self.commit_switcher();
+        true
+    }
+
+    pub fn commit_switcher(&mut self) {
+        let selected = self.switcher.selected_session().cloned();
+        self.hide_ui();
+        // The current session is only listed to show where the cycle starts
+        if let Some(session) = selected
+            && Some(&session) != self.active_session.as_ref()
+        {
+            let slot = self.get_slot_for_session(&session);
+            self.switch_with_hooks(&session, slot);
+        }
+    }
+
+    /// Opens the log scrolled to its newest entries.
+    pub fn open_log(&mut self) {
+        self.log_scroll = usize::MAX;
+        self.modal_mode = ModalMode::Log;
+    }
+
+    // Like the bindings, rendering clamps the scroll to the last page
+    pub fn scroll_log(&mut self, direction: i32) {
+        let max_scroll = self.log.entries().len().saturating_sub(1);
+        if direction > 0 {
+            self.log_scroll = self.log_scroll.saturating_add(1).min(max_scroll);
+        } else if direction < 0 {
+            self.log_scroll = self.log_scroll.saturating_sub(1);
+        }
+    }
+
+    /// Starts or stops counting focus time for this instance's session,
+    /// which is focused while a client is attached to it.
+    pub fn update_focus_tracking(&mut self) {
+        let is_focused = self
+            .sessions
+            .iter()
+            .any(|s| s.is_current_session && s.connected_clients > 0);
+
+        match (is_focused, self.focus_started) {
+            (true, None) => self.focus_started = Some(persistence::unix_now()),
+            (false, Some(_)) => {
+                let result = self.flush_focus_time();
+                self.report(result);
+                self.focus_started = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Persists the focus time counted so far, so a crash loses at most one
+    /// timer interval.
+    pub fn flush_focus_time(&mut self) -> Result<(), HotbarError> {
+        let (Some(started), Some(session)) = (self.focus_started, self.active_session.clone())
+        else {
+            return Ok(());
+        };
+
+        let now = persistence::unix_now();
+        self.load_hotbar_data();
+        self.hotbar_data.add_focus_time(&session, started, now);
+        // Keeps counting from `started` when the save fails
+        self.save_hotbar_data()?;
+        self.focus_started = Some(now);
+        Ok(())
+    }
+
+    /// Flushes focus time on the focus timer. Timer events don't say which
+    /// timer fired, so events before the timer is due are ignored.
+    pub fn on_timer(&mut self) {
+        let now = persistence::unix_now();
+        if now < self.focus_timer_due {
+            return;
+        }
+        let result = self.flush_focus_time();
+        self.report(result);
+        self.focus_timer_due = now + FOCUS_FLUSH_INTERVAL_SECS;
+        self.host.set_timeout(FOCUS_FLUSH_INTERVAL_SECS as f64);
+    }
+
+    pub fn open_stats(&mut self) {
+        let result = self.flush_focus_time();
+        self.report(result);
+        self.load_hotbar_data();
+        self.modal_mode = ModalMode::Stats;
+    }
+
+    pub fn is_typing(&self) -> bool {
+        matches!(
+            self.modal_mode,
+            ModalMode::Filter
+                | ModalMode::EditNote(_)
+                | ModalMode::EditTags(_)
+                | ModalMode::TagMarked
+        )
+    }
+
+    pub fn start_filter(&mut self) {
+        self.input = self.filter.clone();
+        self.modal_mode = ModalMode::Filter;
+    }
+
+    pub fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.rebuild_session_list();
+    }
+
+    pub fn start_edit_note(&mut self) {
+        if let Some(session) = self.get_selected_session().cloned() {
+            self.input = self
+                .hotbar_data
+                .get_metadata(&session)
+                .map(|metadata| metadata.note.clone())
+                .unwrap_or_default();
+            self.modal_mode = ModalMode::EditNote(session);
+        }
+    }
+
+    pub fn start_edit_tags(&mut self) {
+        if let Some(session) = self.get_selected_session().cloned() {
+            self.input = self
+                .hotbar_data
+                .get_metadata(&session)
+                .map(|metadata| metadata.tags.join(" "))
+                .unwrap_or_default();
+            self.modal_mode = ModalMode::EditTags(session);
+        }
+    }
+
+    /// Handles a key while one of the text prompts is open. The filter is
+    /// applied while typing; notes and tags are saved on Enter.
+    pub fn handle_text_key(&mut self, key: &KeyWithModifier) -> Result<(), HotbarError> {
+        match key.bare_key {
+            BareKey::Char(c)
+                if key.has_no_modifiers() || key.has_modifiers(&[KeyModifier::Shift]) =>
+            {
+                self.input.push(c)
+            }
+            BareKey::Backspace => {
+                self.input.pop();
+            }
+            BareKey::Enter => {
+                let result = match std::mem::take(&mut self.modal_mode) {
+                    ModalMode::EditNote(session) => {
+                        self.load_hotbar_data();
+                        self.hotbar_data.set_note(&session, &self.input);
+                        self.save_hotbar_data()
+                    }
+                    ModalMode::TagMarked => {
+                        self.modal_mode = ModalMode::Confirm(PendingChange::TagMarked {
+                            sessions: self.marked.clone(),
+                            tags: std::mem::take(&mut self.input),
+                        });
+                        return Ok(());
+                    }
+                    ModalMode::EditTags(session) => {
+                        self.load_hotbar_data();
+                        self.hotbar_data.set_tags(&session, &self.input);
+                        self.save_hotbar_data()
+                    }
+                    _ => Ok(()),
+                };
+                self.input.clear();
+                self.rebuild_session_list();
+                return result;
+            }
+            BareKey::Esc => {
+                if self.modal_mode == ModalMode::Filter {
+                    self.filter.clear();
+                }
+                self.modal_mode = ModalMode::Normal;
+                self.input.clear();
+                self.rebuild_session_list();
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+
+        if self.modal_mode == ModalMode::Filter {
+            self.filter = self.input.clone();
+            // Unless the selected session still matches
+            self.selected_index = 0;
+            self.rebuild_session_list();
+        }
+        Ok(())
+    }
+
+    pub fn switch_to_slot(&mut self, slot: usize) -> Result<(), HotbarError> {
+        self.check_permissions()?;
+        let reference = self
+            .hotbar_data
+            .get_session_at_slot(slot)
+            .ok_or(HotbarError::MissingSession(SwitchTarget::Slot(slot)))?;
+        let session_name = &self.resolve_slot_target(reference)?;
+        if self.active_session.as_deref() == Some(session_name.as_str()) {
+            self.notify(Level::Info, format!("Already in session: {}", session_name));
+            return Ok(());
+        }
+        self.switch_with_hooks(session_name, Some(slot));
+        Ok(())
+    }
+
+    /// Switches to the next (`direction` 1) or previous (-1) slot holding a
+    /// running session, counting from the current session's slot and
+    /// wrapping around. Empty slots and sessions that aren't running are
+    /// skipped.
+    pub fn cycle_slots(&mut self, direction: i32) -> Result<(), HotbarError> {
+        self.check_permissions()?;
+        let current = self
+            .active_session
+            .as_deref()
+            .and_then(|session| self.get_slot_for_session(session));
+        // Outside the hotbar, the tour starts at either end
+        let (start, steps) = match current {
+            Some(slot) => (slot as i32, 4),
+            None if direction > 0 => (-1, 5),
+            None => (5, 5),
+        };
+
+        let target = (1..=steps)
+            .map(|step| (start + direction * step).rem_euclid(5) as usize)
+            .find(|slot| {
+                self.hotbar_data
+                    .get_session_at_slot(*slot)
+                    .and_then(|reference| self.resolve_slot_target(reference).ok())
+                    .is_some_and(|session| self.is_running(&session))
+            })
+            .ok_or(HotbarError::MissingSession(SwitchTarget::Cycle))?;
+        self.switch_to_slot(target)
     }
 
     pub fn navigate_sessions(&mut self, direction: i32) {
-        let max_index = self.all_sessions.len().saturating_sub(1);
+        let max_index = self.rows.len().saturating_sub(1);
         if direction > 0 {
             self.selected_index = (self.selected_index + 1).min(max_index);
         } else if direction < 0 && self.selected_index > 0 {
             self.selected_index = self.selected_index.saturating_sub(1);
         }
+        // A group header keeps the session remembered from before
+        if let Some(session) = self.get_selected_session() {
+            self.selected_session = Some(session.clone());
+        }
+    }
+
+    fn select_on_open(&mut self) {
+        let current = self.active_session.as_deref();
+        let target = match self.select_on_open {
+            OpenSelection::Current => current.map(str::to_string),
+            OpenSelection::Previous => {
+                let running: Vec<String> = self.sessions.iter().map(|s| s.name.clone()).collect();
+                switcher::recent_sessions(&self.hotbar_data, &running, current)
+                    .into_iter()
+                    .find(|name| Some(name.as_str()) != current)
+                    .or_else(|| current.map(str::to_string))
+            }
+        };
+        if let Some(target) = target {
+            self.select_session(&target);
+        }
+    }
+
+    /// Selects `session_name`, remembering it even when it isn't listed.
+    fn select_session(&mut self, session_name: &str) {
+        self.selected_session = Some(session_name.to_string());
+        if let Some(index) = self.row_of(session_name) {
+            self.selected_index = index;
+        }
+    }
+
+    fn row_of(&self, session_name: &str) -> Option<usize> {
+        self.rows
+            .iter()
+            .position(|row| matches!(row, ListRow::Session { name, .. } if name == session_name))
     }
 
     pub fn adjust_scroll(&mut self, visible_height: usize) {
-        if visible_height == 0 || self.all_sessions.is_empty() {
+        if visible_height == 0 || self.rows.is_empty() {
             return;
         }
 
+        // Collapsing groups can shrink the list below the current window
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.rows.len().saturating_sub(visible_height));
+
         // If selected item is above the visible window, scroll up
         if self.selected_index < self.scroll_offset {
             self.scroll_offset = self.selected_index;
@@ -183,26 +1150,142 @@ impl HotbarManager {
     }
 
     pub fn can_scroll_down(&self, visible_height: usize) -> bool {
-        self.scroll_offset + visible_height < self.all_sessions.len()
+        self.scroll_offset + visible_height < self.rows.len()
+    }
+
+    pub fn update_session_list(&mut self, mut sessions: Vec<SessionInfo>) {
+        sessions.sort_by(|a, b| a.name.cmp(&b.name));
+        self.sessions = sessions;
+        self.rebuild_session_list();
     }
 
-    pub fn update_session_list(&mut self, sessions: Vec<zellij_tile::prelude::SessionInfo>) {
-        self.all_sessions = sessions.iter().map(|s| s.name.clone()).collect();
-        self.all_sessions.sort();
+    pub fn set_projects(&mut self, projects: Vec<Project>) {
+        self.projects = projects;
+        self.rebuild_session_list();
+    }
+
+    pub fn is_running(&self, session_name: &str) -> bool {
+        self.session_info(session_name).is_some()
+    }
+
+    pub fn session_info(&self, session_name: &str) -> Option<&SessionInfo> {
+        self.sessions.iter().find(|s| s.name == session_name)
+    }
+
+    pub fn project_for(&self, session_name: &str) -> Option<&Project> {
+        self.projects.iter().find(|p| p.name == session_name)
+    }
 
-        if self.selected_index >= self.all_sessions.len() && !self.all_sessions.is_empty() {
-            self.selected_index = self.all_sessions.len() - 1;
+    // Running sessions come first, followed by discovered projects that don't
+    // have a session yet.
+    fn rebuild_session_list(&mut self) {
+        let not_started: Vec<String> = self
+            .projects
+            .iter()
+            .filter(|p| !self.is_running(&p.name))
+            .map(|p| p.name.clone())
+            .collect();
+
+        self.all_sessions = self.sessions.iter().map(|s| s.name.clone()).collect();
+        self.all_sessions.extend(not_started);
+        if !self.filter.is_empty() {
+            let metadata = &self.hotbar_data.metadata;
+            let query = &self.filter;
+            self.all_sessions
+                .retain(|name| filter::matches(query, name, metadata.get(name)));
+        }
+
+        self.rebuild_rows();
+    }
+
+    fn rebuild_rows(&mut self) {
+        self.rows = grouping::build_rows(
+            &self.all_sessions,
+            self.group_by,
+            &self.group_separator,
+            &self.hotbar_data.metadata,
+            &self.collapsed_groups,
+        );
+
+        // The remembered session may be filtered out, in a collapsed group
+        // or gone, leaving the selection where it was
+        if let Some(index) = self
+            .selected_session
+            .as_deref()
+            .and_then(|name| self.row_of(name))
+        {
+            self.selected_index = index;
+        } else if self.selected_index >= self.rows.len() && !self.rows.is_empty() {
+            self.selected_index = self.rows.len() - 1;
         }
     }
 
     pub fn get_selected_session(&self) -> Option<&String> {
-        self.all_sessions.get(self.selected_index)
+        match self.rows.get(self.selected_index)? {
+            ListRow::Session { name, .. } => Some(name),
+            ListRow::Group { .. } => None,
+        }
+    }
+
+    pub fn cycle_group_by(&mut self) {
+        self.group_by = self.group_by.next();
+        self.selected_index = 0;
+        self.rebuild_rows();
+    }
+
+    /// Collapses the selected group, or the group the selected session is
+    /// in, and moves the selection to its header.
+    pub fn collapse_selected_group(&mut self) {
+        let header = match self.rows.get(self.selected_index) {
+            Some(ListRow::Group { .. }) => self.selected_index,
+            Some(ListRow::Session { grouped: true, .. }) => {
+                let Some(header) = self.rows[..self.selected_index]
+                    .iter()
+                    .rposition(|row| matches!(row, ListRow::Group { .. }))
+                else {
+                    return;
+                };
+                header
+            }
+            _ => return,
+        };
+
+        if let ListRow::Group { name, .. } = &self.rows[header] {
+            self.collapsed_groups.insert(name.clone());
+        }
+        self.selected_index = header;
+        self.rebuild_rows();
+    }
+
+    pub fn expand_selected_group(&mut self) {
+        if let Some(ListRow::Group { name, .. }) = self.rows.get(self.selected_index) {
+            let name = name.clone();
+            self.collapsed_groups.remove(&name);
+            self.rebuild_rows();
+        }
+    }
+
+    /// Resolves what a slot points at to a session name. Slots following a
+    /// repository's main worktree (`repo/`) resolve to whichever checkout
+    /// discovery currently reports for it, and to nothing until it does.
+    pub fn resolve_slot_target(&self, reference: &str) -> Result<String, HotbarError> {
+        let Some(repo) = discovery::main_worktree_repo(reference) else {
+            return Ok(reference.to_string());
+        };
+
+        discovery::main_worktree_of(&self.projects, repo)
+            .map(|p| p.name.clone())
+            .ok_or_else(|| {
+                HotbarError::MissingSession(SwitchTarget::MainWorktree(repo.to_string()))
+            })
     }
 
     pub fn get_slot_for_session(&self, session_name: &str) -> Option<usize> {
         for i in 0..5 {
             if let Some(slot_session) = self.hotbar_data.get_session_at_slot(i)
-                && slot_session == session_name
+                && self
+                    .resolve_slot_target(slot_session)
+                    .is_ok_and(|target| target == session_name)
             {
                 return Some(i);
             }
@@ -210,21 +1293,875 @@ impl HotbarManager {
         None
     }
 
-    pub fn assign_selected_to_slot(&mut self, slot: usize) {
+    pub fn assign_selected_to_slot(&mut self, slot: usize) -> Result<(), HotbarError> {
+        if slot >= 5 {
+            return Err(HotbarError::InvalidSlot((slot + 1).to_string()));
+        }
         if let Some(session) = self.get_selected_session() {
-            let session = session.clone();
-            self.hotbar_data
-                .set_session_at_slot(slot, Some(session.clone()));
-            self.save_hotbar_data();
-            eprintln!("Assigned '{}' to slot {}", session, slot + 1);
+            // Main checkouts are stored as a reference to the repository so
+            // the slot keeps following them when their branch changes
+            let session = match self.project_for(session) {
+                Some(project) if project.is_main_worktree => project.main_worktree_ref(),
+                _ => session.clone(),
+            };
+
+            let current = self.hotbar_data.get_session_at_slot(slot);
+            if self.hotbar_data.is_locked(slot) && current.is_some_and(|c| *c != session) {
+                self.modal_mode = ModalMode::Confirm(PendingChange::Assign { slot, session });
+                return Ok(());
+            }
+            self.assign_to_slot(slot, session)?;
+        }
+        Ok(())
+    }
+
+    fn assign_to_slot(&mut self, slot: usize, session: String) -> Result<(), HotbarError> {
+        self.hotbar_data
+            .edit_slots(&[(slot, Some(session.clone()))]);
+        self.save_hotbar_data()?;
+        self.notify(
+            Level::Info,
+            format!("Assigned '{}' to slot {}", session, slot + 1),
+        );
+        Ok(())
+    }
+
+    pub fn remove_selected_from_hotbar(&mut self) -> Result<(), HotbarError> {
+        if let Some(session) = self.get_selected_session()
+            && let Some(slot) = self.get_slot_for_session(session)
+        {
+            if self.hotbar_data.is_locked(slot) {
+                self.modal_mode = ModalMode::Confirm(PendingChange::Remove { slot });
+                return Ok(());
+            }
+            self.remove_from_slot(slot)?;
         }
+        Ok(())
     }
 
-    pub fn remove_selected_from_hotbar(&mut self) {
+    pub fn toggle_selected_slot_lock(&mut self) -> Result<(), HotbarError> {
         if let Some(session) = self.get_selected_session()
             && let Some(slot) = self.get_slot_for_session(session)
         {
-            self.remove_from_slot(slot);
+            self.hotbar_data.toggle_lock(slot);
+            self.save_hotbar_data()?;
+            let state = if self.hotbar_data.is_locked(slot) {
+                "locked"
+            } else {
+                "unlocked"
+            };
+            self.notify(Level::Info, format!("Slot {} {}", slot + 1, state));
+        }
+        Ok(())
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let Some(session) = self.get_selected_session().cloned() else {
+            return;
+        };
+        match self.marked.iter().position(|marked| *marked == session) {
+            Some(index) => {
+                self.marked.remove(index);
+            }
+            None => self.marked.push(session),
+        }
+    }
+
+    pub fn is_marked(&self, session_name: &str) -> bool {
+        self.marked.iter().any(|marked| marked == session_name)
+    }
+
+    /// Plans putting the marked sessions into the free slots from `slot`
+    /// on, in the order they were marked, for confirmation. Sessions already
+    /// in the hotbar stay where they are; locked slots are never filled.
+    pub fn assign_marked_from(&mut self, slot: usize) {
+        self.load_hotbar_data();
+        let mut free_slots = (slot..5).filter(|slot| {
+            self.hotbar_data.get_session_at_slot(*slot).is_none()
+                && !self.hotbar_data.is_locked(*slot)
+        });
+        let mut assignments = Vec::new();
+        let mut left_out = Vec::new();
+        for session in &self.marked {
+            if self.get_slot_for_session(session).is_some() {
+                continue;
+            }
+            match free_slots.next() {
+                Some(slot) => assignments.push((slot, session.clone())),
+                None => left_out.push(session.clone()),
+            }
+        }
+
+        if assignments.is_empty() {
+            self.notify(
+                Level::Info,
+                format!(
+                    "No free slot for the marked sessions from slot {}",
+                    slot + 1
+                ),
+            );
+            return;
+        }
+        self.modal_mode = ModalMode::Confirm(PendingChange::AssignMarked {
+            assignments,
+            left_out,
+        });
+    }
+
+    /// Asks to confirm removing every marked session from the hotbar,
+    /// locked slots included.
+    pub fn remove_marked(&mut self) {
+        self.load_hotbar_data();
+        let slots: Vec<(usize, String)> = self
+            .marked
+            .iter()
+            .filter_map(|session| {
+                let slot = self.get_slot_for_session(session)?;
+                Some((slot, self.hotbar_data.get_session_at_slot(slot)?.clone()))
+            })
+            .collect();
+        if slots.is_empty() {
+            self.notify(
+                Level::Info,
+                "None of the marked sessions are in the hotbar".to_string(),
+            );
+            return;
+        }
+        self.modal_mode = ModalMode::Confirm(PendingChange::RemoveMarked(slots));
+    }
+
+    pub fn start_tag_marked(&mut self) {
+        self.input.clear();
+        self.modal_mode = ModalMode::TagMarked;
+    }
+
+    /// Asks to confirm killing the marked sessions, or the selected one
+    /// when none are marked. Only running sessions other than the current
+    /// one can be killed.
+    pub fn kill_marked(&mut self) {
+        let targets = if self.marked.is_empty() {
+            self.get_selected_session().cloned().into_iter().collect()
+        } else {
+            self.marked.clone()
+        };
+        let sessions: Vec<String> = targets
+            .into_iter()
+            .filter(|session| {
+                self.is_running(session) && Some(session) != self.active_session.as_ref()
+            })
+            .collect();
+        if sessions.is_empty() {
+            self.notify(
+                Level::Info,
+                "Only running sessions other than the current one can be killed".to_string(),
+            );
+            return;
+        }
+        self.modal_mode = ModalMode::Confirm(PendingChange::KillSessions(sessions));
+    }
+
+    /// Waits for `y` before applying `change`, opening the modal when the
+    /// change came from a keybinding or pipe.
+    fn request_confirmation(&mut self, change: PendingChange) {
+        self.modal_mode = ModalMode::Confirm(change);
+        if !self.is_visible {
+            self.show_ui();
+        }
+    }
+
+    pub fn is_confirming(&self) -> bool {
+        matches!(self.modal_mode, ModalMode::Confirm(_))
+    }
+
+    pub fn confirm_pending_change(&mut self) -> Result<(), HotbarError> {
+        match std::mem::take(&mut self.modal_mode) {
+            ModalMode::Confirm(PendingChange::Assign { slot, session }) => {
+                self.assign_to_slot(slot, session)
+            }
+            ModalMode::Confirm(PendingChange::Remove { slot }) => self.remove_from_slot(slot),
+            // Another instance may have changed the slots since the plan was
+            // made, so it's checked again against the data file
+            ModalMode::Confirm(PendingChange::AssignMarked { assignments, .. }) => {
+                self.load_hotbar_data();
+                let planned = assignments.len();
+                let edits: Vec<(usize, Option<String>)> = assignments
+                    .into_iter()
+                    .filter(|(slot, session)| {
+                        self.hotbar_data.get_session_at_slot(*slot).is_none()
+                            && !self.hotbar_data.is_locked(*slot)
+                            && self.get_slot_for_session(session).is_none()
+                    })
+                    .map(|(slot, session)| (slot, Some(session)))
+                    .collect();
+                self.edit_marked_slots(&edits, planned, "Assigned")
+            }
+            ModalMode::Confirm(PendingChange::RemoveMarked(slots)) => {
+                self.load_hotbar_data();
+                let planned = slots.len();
+                let edits: Vec<(usize, Option<String>)> = slots
+                    .into_iter()
+                    .filter(|(slot, session)| {
+                        self.hotbar_data.get_session_at_slot(*slot) == Some(session)
+                    })
+                    .map(|(slot, _)| (slot, None))
+                    .collect();
+                self.edit_marked_slots(&edits, planned, "Removed")
+            }
+            ModalMode::Confirm(PendingChange::TagMarked { sessions, tags }) => {
+                self.load_hotbar_data();
+                for session in &sessions {
+                    let existing = self
+                        .hotbar_data
+                        .get_metadata(session)
+                        .map(|metadata| metadata.tags.join(" "))
+                        .unwrap_or_default();
+                    self.hotbar_data
+                        .set_tags(session, &format!("{} {}", existing, tags));
+                }
+                self.save_hotbar_data()?;
+                self.marked.clear();
+                self.rebuild_session_list();
+                self.notify(Level::Info, format!("Tagged {} sessions", sessions.len()));
+                Ok(())
+            }
+            ModalMode::Confirm(PendingChange::Undo(slots)) => self.undo_confirmed(&slots),
+            ModalMode::Confirm(PendingChange::Redo(slots)) => self.redo_confirmed(&slots),
+            ModalMode::Confirm(PendingChange::RestoreBackup {
+                timestamp,
+                edits,
+                locked,
+            }) => self.restore_slots(timestamp, edits, &locked),
+            ModalMode::Confirm(PendingChange::KillSessions(sessions)) => {
+                self.check_permissions()?;
+                self.host.kill_sessions(&sessions);
+                self.log
+                    .info(format!("Killed sessions: {}", sessions.join(", ")));
+                self.marked.clear();
+                self.notify(Level::Info, format!("Killed {} sessions", sessions.len()));
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // One journal entry for the whole bulk edit, so a single undo reverts it.
+    // Edits dropped since `planned` were confirmed are reported as skipped.
+    fn edit_marked_slots(
+        &mut self,
+        edits: &[(usize, Option<String>)],
+        planned: usize,
+        verb: &str,
+    ) -> Result<(), HotbarError> {
+        self.hotbar_data.edit_slots(edits);
+        self.save_hotbar_data()?;
+        self.marked.clear();
+        let mut message = format!("{} {} sessions", verb, edits.len());
+        if edits.len() < planned {
+            message.push_str(&format!(
+                ", skipped {} whose slots changed meanwhile",
+                planned - edits.len()
+            ));
+        }
+        self.notify(Level::Info, message);
+        Ok(())
+    }
+
+    pub fn cancel_pending_change(&mut self) {
+        self.modal_mode = ModalMode::Normal;
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::geometry::{self, PaneRect};
+    use crate::host::testing::{HostCall, RecordingHost};
+    use crate::notify::Verbosity;
+
+    /// A manager recording its host calls and saving to a data file of its
+    /// own, so tests don't share state with each other or a running zellij.
+    pub fn test_manager(name: &str) -> (HotbarManager, RecordingHost) {
+        let host = RecordingHost::default();
+        let manager = HotbarManager {
+            host: Box::new(host.clone()),
+            data_file: DataFile::temp(name),
+            // Tests that check notifications turn them back on
+            notifications: NotificationConfig {
+                verbosity: Verbosity::Off,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        (manager, host)
+    }
+
+    pub fn session(name: &str) -> SessionInfo {
+        SessionInfo {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn slot(manager: &HotbarManager, slot: usize) -> Option<&str> {
+        manager
+            .hotbar_data
+            .get_session_at_slot(slot)
+            .map(String::as_str)
+    }
+
+    fn with_sessions(manager: &mut HotbarManager, names: &[&str]) {
+        manager.update_session_list(names.iter().map(|name| session(name)).collect());
+    }
+
+    #[test]
+    fn assigns_selected_session_to_slot() {
+        let (mut manager, _host) = test_manager("assign");
+        with_sessions(&mut manager, &["api", "docs", "web"]);
+
+        manager.navigate_sessions(1);
+        manager.assign_selected_to_slot(2).unwrap();
+
+        assert_eq!(slot(&manager, 2), Some("docs"));
+        let saved = manager.data_file.load().unwrap();
+        assert_eq!(
+            saved.get_session_at_slot(2).map(String::as_str),
+            Some("docs")
+        );
+        assert_eq!(manager.get_slot_for_session("docs"), Some(2));
+    }
+
+    #[test]
+    fn locked_slot_waits_for_confirmation() {
+        let (mut manager, _host) = test_manager("locked");
+        with_sessions(&mut manager, &["api", "docs"]);
+
+        manager.assign_selected_to_slot(0).unwrap();
+        manager.toggle_selected_slot_lock().unwrap();
+        manager.navigate_sessions(1);
+        manager.assign_selected_to_slot(0).unwrap();
+
+        assert!(manager.is_confirming());
+        assert_eq!(slot(&manager, 0), Some("api"));
+
+        manager.confirm_pending_change().unwrap();
+        assert_eq!(slot(&manager, 0), Some("docs"));
+    }
+
+    #[test]
+    fn undo_of_a_locked_slot_waits_for_confirmation() {
+        let (mut manager, _host) = test_manager("locked_undo");
+        with_sessions(&mut manager, &["api"]);
+        manager.assign_selected_to_slot(0).unwrap();
+        manager.toggle_selected_slot_lock().unwrap();
+
+        manager.undo().unwrap();
+        assert!(manager.is_visible);
+        assert!(manager.modal_mode == ModalMode::Confirm(PendingChange::Undo(vec![0])));
+        assert_eq!(slot(&manager, 0), Some("api"));
+
+        manager.confirm_pending_change().unwrap();
+        assert_eq!(slot(&manager, 0), None);
+
+        // Redoing puts the session back into the still locked slot
+        manager.redo().unwrap();
+        assert!(manager.is_confirming());
+        manager.cancel_pending_change();
+        assert_eq!(slot(&manager, 0), None);
+    }
+
+    #[test]
+    fn restoring_over_a_locked_slot_waits_for_confirmation() {
+        let (mut manager, _host) = test_manager("locked_restore");
+        with_sessions(&mut manager, &["api", "docs"]);
+        manager.assign_selected_to_slot(0).unwrap();
+        manager.navigate_sessions(1);
+        manager.assign_selected_to_slot(1).unwrap();
+        manager.toggle_selected_slot_lock().unwrap();
+
+        // The backup from before `docs` went into slot 2
+        manager.restore_backup(0).unwrap();
+        assert!(manager.is_confirming());
+        assert_eq!(slot(&manager, 1), Some("docs"));
+
+        manager.confirm_pending_change().unwrap();
+        assert_eq!(slot(&manager, 0), Some("api"));
+        assert_eq!(slot(&manager, 1), None);
+    }
+
+    #[test]
+    fn switches_to_slot_session() {
+        let (mut manager, host) = test_manager("switch_slot");
+        with_sessions(&mut manager, &["api", "docs"]);
+        manager.set_active_session("api");
+        manager.assign_selected_to_slot(0).unwrap();
+        manager.navigate_sessions(1);
+        manager.assign_selected_to_slot(1).unwrap();
+
+        manager.switch_to_slot(0).unwrap();
+        manager.switch_to_slot(1).unwrap();
+        assert!(matches!(
+            manager.switch_to_slot(4),
+            Err(HotbarError::MissingSession(SwitchTarget::Slot(4)))
+        ));
+
+        // Slot 1 holds the active session and slot 5 is empty
+        assert_eq!(host.switched_sessions(), vec!["docs".to_string()]);
+        assert!(
+            host.calls()
+                .contains(&HostCall::SwitchToInputMode(InputMode::Normal))
+        );
+    }
+
+    #[test]
+    fn granting_permissions_starts_a_scan() {
+        let (mut manager, host) = test_manager("granted_scan");
+        manager.discovery.roots = vec!["/src".to_string()];
+
+        manager.set_permission_status(PermissionStatus::Granted);
+        assert!(host.calls().iter().any(|call| matches!(
+            call,
+            HostCall::RunCommand { context, .. } if discovery::is_scan_result(context)
+        )));
+    }
+
+    #[test]
+    fn only_the_session_list_starts_a_scan() {
+        let (mut manager, host) = test_manager("open_scan");
+        manager.discovery.roots = vec!["/src".to_string()];
+        with_sessions(&mut manager, &["api", "docs"]);
+        manager.set_active_session("docs");
+        manager.set_active_session("api");
+        let scans =
+            || {
+                host.calls()
+                .iter()
+                .filter(|call| matches!(
+                    call,
+                    HostCall::RunCommand { context, .. } if discovery::is_scan_result(context)
+                ))
+                .count()
+            };
+
+        manager.trigger_switcher().unwrap();
+        assert_eq!(scans(), 0);
+
+        manager.hide_ui();
+        manager.show_ui();
+        assert_eq!(scans(), 1);
+    }
+
+    #[test]
+    fn main_worktree_slot_waits_for_discovery() {
+        let (mut manager, host) = test_manager("main_worktree_slot");
+        // A session that merely ends in the worktree separator
+        with_sessions(&mut manager, &["notes@"]);
+        manager.hotbar_data.edit_slots(&[
+            (0, Some("api/".to_string())),
+            (1, Some("notes@".to_string())),
+        ]);
+
+        assert!(matches!(
+            manager.switch_to_slot(0),
+            Err(HotbarError::MissingSession(SwitchTarget::MainWorktree(repo))) if repo == "api"
+        ));
+        manager.switch_to_slot(1).unwrap();
+
+        manager.set_projects(vec![Project {
+            name: "api@main".to_string(),
+            path: "/src/api".into(),
+            repo: "api".to_string(),
+            branch: Some("main".to_string()),
+            is_main_worktree: true,
+        }]);
+        manager.switch_to_slot(0).unwrap();
+        assert_eq!(
+            host.switched_sessions(),
+            vec!["notes@".to_string(), "api@main".to_string()]
+        );
+    }
+
+    #[test]
+    fn tracks_previous_session() {
+        let (mut manager, host) = test_manager("previous");
+
+        assert!(manager.set_active_session("api"));
+        assert!(manager.set_active_session("docs"));
+        assert!(!manager.set_active_session("docs"));
+        assert_eq!(manager.hotbar_data.previous_session.as_deref(), Some("api"));
+
+        manager.switch_to_previous_session().unwrap();
+        assert_eq!(host.switched_sessions(), vec!["api".to_string()]);
+    }
+
+    #[test]
+    fn before_switch_hook_delays_switch() {
+        let (mut manager, host) = test_manager("hooks");
+        let configuration = [(
+            hooks::BEFORE_SWITCH_KEY.to_string(),
+            "echo leaving".to_string(),
+        )]
+        .into();
+        manager.hooks = HookConfig::from_configuration(&configuration);
+        manager.set_active_session("api");
+
+        manager.switch_with_hooks("docs", Some(0));
+        // Followed by the timer for its timeout
+        let calls = host.calls();
+        let context = match calls.iter().rev().nth(1) {
+            Some(HostCall::RunCommand {
+                command, context, ..
+            }) => {
+                assert_eq!(command, &["sh", "-c", "echo leaving"]);
+                context.clone()
+            }
+            other => panic!("expected the hook to run, got {:?}", other),
+        };
+        assert!(host.switched_sessions().is_empty());
+
+        let hook = hooks::parse_hook_result(&context).unwrap();
+        manager.handle_hook_result(Some(0), b"", hook);
+        assert_eq!(host.switched_sessions(), vec!["docs".to_string()]);
+    }
+
+    #[test]
+    fn failing_before_switch_hook_cancels_switch() {
+        let (mut manager, host) = test_manager("hook_fails");
+        let configuration = [(hooks::BEFORE_SWITCH_KEY.to_string(), "false".to_string())].into();
+        manager.hooks = HookConfig::from_configuration(&configuration);
+        manager.set_active_session("api");
+
+        manager.switch_with_hooks("docs", Some(0));
+        let hook = HookResult {
+            stage: HookStage::BeforeSwitch,
+            target: "docs".to_string(),
+            slot: Some(0),
+        };
+        manager.handle_hook_result(Some(1), b"not now", hook);
+        assert!(host.switched_sessions().is_empty());
+
+        // Nor does the timer switch once the switch was cancelled
+        assert!(!manager.expire_waiting_switch());
+        assert!(host.switched_sessions().is_empty());
+    }
+
+    #[test]
+    fn switches_anyway_when_the_before_switch_hook_never_reports() {
+        let (mut manager, host) = test_manager("hook_timeout");
+        let configuration = [
+            (hooks::BEFORE_SWITCH_KEY.to_string(), "sleep 60".to_string()),
+            (
+                hooks::BEFORE_SWITCH_TIMEOUT_KEY.to_string(),
+                "2000".to_string(),
+            ),
+        ]
+        .into();
+        manager.hooks = HookConfig::from_configuration(&configuration);
+        manager.set_active_session("api");
+
+        manager.switch_with_hooks("docs", Some(0));
+        assert!(host.calls().contains(&HostCall::SetTimeout(2.0)));
+        assert!(!manager.expire_waiting_switch());
+        assert!(host.switched_sessions().is_empty());
+
+        // No RunCommandResult arrives before the timer fires
+        manager.waiting_switch.as_mut().unwrap().deadline_ms = 0;
+        assert!(manager.expire_waiting_switch());
+        assert_eq!(host.switched_sessions(), vec!["docs".to_string()]);
+
+        // Nor does a late result switch a second time
+        let hook = HookResult {
+            stage: HookStage::BeforeSwitch,
+            target: "docs".to_string(),
+            slot: Some(0),
+        };
+        manager.handle_hook_result(Some(0), b"", hook);
+        assert_eq!(host.switched_sessions(), vec!["docs".to_string()]);
+    }
+
+    #[test]
+    fn scrolls_to_keep_selection_visible() {
+        let (mut manager, _host) = test_manager("scroll");
+        let names: Vec<String> = (0..10).map(|i| format!("session-{}", i)).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        with_sessions(&mut manager, &names);
+
+        for _ in 0..7 {
+            manager.navigate_sessions(1);
+        }
+        manager.adjust_scroll(5);
+        assert_eq!(manager.selected_index, 7);
+        assert_eq!(manager.scroll_offset, 3);
+        assert!(manager.can_scroll_up());
+        assert!(manager.can_scroll_down(5));
+
+        for _ in 0..5 {
+            manager.navigate_sessions(1);
+        }
+        manager.adjust_scroll(5);
+        assert_eq!(manager.selected_index, 9);
+        assert_eq!(manager.scroll_offset, 5);
+        assert!(!manager.can_scroll_down(5));
+
+        for _ in 0..9 {
+            manager.navigate_sessions(-1);
+        }
+        manager.adjust_scroll(5);
+        assert_eq!(manager.scroll_offset, 0);
+        assert!(!manager.can_scroll_up());
+    }
+
+    #[test]
+    fn shows_and_hides_modal() {
+        let (mut manager, host) = test_manager("visibility");
+
+        manager.show_ui();
+        assert!(manager.is_visible);
+        manager.hide_ui();
+        assert!(!manager.is_visible);
+
+        assert_eq!(
+            host.calls(),
+            vec![
+                HostCall::ShowSelf,
+                HostCall::SetSelectable(true),
+                HostCall::HideSelf,
+                HostCall::SetSelectable(false),
+            ]
+        );
+    }
+
+    #[test]
+    fn notifications_show_briefly_while_hidden() {
+        let (mut manager, host) = test_manager("notifications");
+        manager.notifications.verbosity = Verbosity::Errors;
+
+        // Info messages are filtered out at this verbosity
+        with_sessions(&mut manager, &["api"]);
+        manager.set_active_session("api");
+        manager.assign_selected_to_slot(0).unwrap();
+        manager.switch_to_slot(0).unwrap();
+        assert_eq!(manager.notification, None);
+
+        let result = manager.switch_to_slot(1);
+        manager.report(result);
+        assert!(manager.showing_toast);
+        assert_eq!(
+            manager.notification.as_ref().map(|n| n.level),
+            Some(Level::Error)
+        );
+        assert!(!manager.expire_notification());
+
+        manager.notification.as_mut().unwrap().expires_at = 0;
+        assert!(manager.expire_notification());
+        assert!(!manager.is_visible);
+        assert_eq!(
+            host.calls(),
+            vec![
+                HostCall::ShowSelf,
+                HostCall::SetSelectable(false),
+                HostCall::SetTimeout(3.0),
+                HostCall::HideSelf,
+                HostCall::SetSelectable(false),
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles_through_running_slot_sessions() {
+        let (mut manager, host) = test_manager("cycle");
+        manager.hotbar_data.edit_slots(&[
+            (0, Some("api".to_string())),
+            (2, Some("gone".to_string())),
+            (3, Some("docs".to_string())),
+        ]);
+        manager.save_hotbar_data().unwrap();
+        with_sessions(&mut manager, &["api", "docs", "web"]);
+
+        manager.set_active_session("api");
+        manager.cycle_slots(1).unwrap();
+        manager.set_active_session("docs");
+        manager.cycle_slots(1).unwrap();
+        manager.cycle_slots(-1).unwrap();
+        // Not in the hotbar: forward starts at slot 1, backward at slot 5
+        manager.set_active_session("web");
+        manager.cycle_slots(1).unwrap();
+        manager.cycle_slots(-1).unwrap();
+
+        assert_eq!(
+            host.switched_sessions(),
+            vec!["docs", "api", "api", "api", "docs"]
+        );
+
+        manager.set_active_session("api");
+        manager.hotbar_data.edit_slots(&[(3, None)]);
+        assert!(matches!(
+            manager.cycle_slots(1),
+            Err(HotbarError::MissingSession(SwitchTarget::Cycle))
+        ));
+    }
+
+    fn accessed_at(manager: &mut HotbarManager, sessions: &[(&str, u64)]) {
+        for (name, last_accessed) in sessions {
+            manager
+                .hotbar_data
+                .metadata
+                .entry(name.to_string())
+                .or_default()
+                .last_accessed = *last_accessed;
+        }
+        manager.save_hotbar_data().unwrap();
+    }
+
+    #[test]
+    fn switcher_cycles_recent_sessions_and_switches_after_a_pause() {
+        let (mut manager, host) = test_manager("switcher");
+        with_sessions(&mut manager, &["api", "docs", "new", "web"]);
+        manager.set_active_session("web");
+        accessed_at(&mut manager, &[("api", 10), ("docs", 30), ("web", 20)]);
+
+        manager.trigger_switcher().unwrap();
+        assert_eq!(manager.switcher.sessions, vec!["web", "docs", "api", "new"]);
+        assert_eq!(manager.switcher.selected_session().unwrap(), "docs");
+
+        manager.trigger_switcher().unwrap();
+        // Still triggering, so nothing is switched to yet
+        assert!(!manager.commit_switcher_if_due());
+        manager.switcher.expire();
+        assert!(manager.commit_switcher_if_due());
+
+        assert_eq!(host.switched_sessions(), vec!["api"]);
+        assert!(!manager.is_visible);
+        assert!(manager.modal_mode == ModalMode::Normal);
+    }
+
+    #[test]
+    fn places_the_pane_once_the_tab_size_is_known() {
+        let (mut manager, host) = test_manager("geometry");
+        let configuration = [(geometry::MODAL_ANCHOR_KEY, "top")]
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        manager.geometry = ModalGeometry::from_configuration(&configuration);
+
+        manager.show_ui();
+        manager.set_viewport(40, 100);
+        manager.hide_ui();
+        manager.show_ui();
+
+        let placements: Vec<HostCall> = host
+            .calls()
+            .into_iter()
+            .filter(|call| matches!(call, HostCall::ChangeFloatingPaneCoordinates(_)))
+            .collect();
+        let top_half = PaneRect {
+            x: 25,
+            y: 0,
+            width: 50,
+            height: 20,
+        };
+        assert_eq!(
+            placements,
+            vec![
+                HostCall::ChangeFloatingPaneCoordinates(top_half),
+                HostCall::ChangeFloatingPaneCoordinates(top_half),
+            ]
+        );
+    }
+
+    #[test]
+    fn opens_on_the_current_session_and_keeps_it_selected() {
+        let (mut manager, _host) = test_manager("open_selection");
+        with_sessions(&mut manager, &["api", "docs", "web"]);
+        manager.set_active_session("web");
+
+        manager.show_ui();
+        assert_eq!(manager.get_selected_session().unwrap(), "web");
+
+        manager.filter = "a".to_string();
+        manager.rebuild_session_list();
+        assert_eq!(manager.get_selected_session().unwrap(), "api");
+        manager.clear_filter();
+        assert_eq!(manager.get_selected_session().unwrap(), "web");
+        manager.cycle_group_by();
+        assert_eq!(manager.get_selected_session().unwrap(), "web");
+
+        manager.hide_ui();
+        manager.select_on_open = OpenSelection::Previous;
+        accessed_at(&mut manager, &[("api", 10), ("docs", 20)]);
+        manager.show_ui();
+        assert_eq!(manager.get_selected_session().unwrap(), "docs");
+    }
+
+    #[test]
+    fn bulk_actions_apply_to_marked_sessions_after_confirmation() {
+        let (mut manager, host) = test_manager("bulk");
+        with_sessions(&mut manager, &["api", "docs", "old", "web"]);
+        manager.set_active_session("web");
+        manager
+            .hotbar_data
+            .edit_slots(&[(1, Some("old".to_string()))]);
+        manager.save_hotbar_data().unwrap();
+
+        // api, docs and web, skipping old
+        for row in 0..4 {
+            manager.selected_index = row;
+            manager.toggle_mark();
         }
+        manager.selected_index = 2;
+        manager.toggle_mark();
+        assert_eq!(manager.marked, vec!["api", "docs", "web"]);
+
+        manager.assign_marked_from(0);
+        assert!(manager.is_confirming());
+        manager.confirm_pending_change().unwrap();
+        assert_eq!(
+            (0..5).map(|i| slot(&manager, i)).collect::<Vec<_>>(),
+            vec![Some("api"), Some("old"), Some("docs"), Some("web"), None]
+        );
+        assert!(manager.marked.is_empty());
+        manager.undo().unwrap();
+        assert_eq!(slot(&manager, 0), None);
+
+        // The current session is never killed
+        manager.marked = vec!["old".to_string(), "web".to_string()];
+        manager.kill_marked();
+        manager.confirm_pending_change().unwrap();
+        assert!(
+            host.calls()
+                .contains(&HostCall::KillSessions(vec!["old".to_string()]))
+        );
+
+        manager.marked = vec!["api".to_string(), "docs".to_string()];
+        manager.hotbar_data.set_tags("api", "team");
+        manager.start_tag_marked();
+        manager.input = "#team, backend".to_string();
+        manager
+            .handle_text_key(&KeyWithModifier::new(BareKey::Enter))
+            .unwrap();
+        manager.confirm_pending_change().unwrap();
+        let tags = |name: &str| manager.hotbar_data.get_metadata(name).unwrap().tags.clone();
+        assert_eq!(tags("api"), vec!["team", "backend"]);
+        assert_eq!(tags("docs"), vec!["team", "backend"]);
+    }
+
+    #[test]
+    fn bulk_assignment_skips_slots_changed_before_confirmation() {
+        let (mut manager, _host) = test_manager("bulk_stale");
+        with_sessions(&mut manager, &["api", "docs", "web"]);
+        manager.marked = vec!["api".to_string(), "docs".to_string(), "web".to_string()];
+        manager.assign_marked_from(0);
+        assert!(manager.is_confirming());
+
+        // Another instance fills slot 2 and locks slot 3 in the meantime
+        let mut other = HotbarData::default();
+        other.edit_slots(&[(1, Some("notes".to_string()))]);
+        other.toggle_lock(2);
+        manager.data_file.save(&other).unwrap();
+
+        manager.confirm_pending_change().unwrap();
+        assert_eq!(
+            (0..5).map(|i| slot(&manager, i)).collect::<Vec<_>>(),
+            vec![Some("api"), Some("notes"), None, None, None]
+        );
     }
 }
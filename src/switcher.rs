@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use crate::persistence::HotbarData;
+
+pub const SWITCHER_DELAY_KEY: &str = "switcher_delay_ms";
+// Long enough to trigger again before the switch happens, short enough not
+// to feel like waiting
+const DEFAULT_SWITCHER_DELAY_MS: u64 = 600;
+
+/// The Alt-Tab style list of recently used sessions. Each trigger moves the
+/// selection on; once the triggers stop for `delay_ms` the selected session
+/// is switched to.
+pub struct Switcher {
+    pub delay_ms: u64,
+    /// Most recently used first, starting with the current session
+    pub sessions: Vec<String>,
+    pub selected: usize,
+    /// Unix time in milliseconds when the selection is committed
+    commit_at: u64,
+}
+
+impl Default for Switcher {
+    fn default() -> Self {
+        Self {
+            delay_ms: DEFAULT_SWITCHER_DELAY_MS,
+            sessions: Vec::new(),
+            selected: 0,
+            commit_at: 0,
+        }
+    }
+}
+
+impl Switcher {
+    pub fn from_configuration(configuration: &BTreeMap<String, String>) -> Self {
+        Self {
+            delay_ms: configuration
+                .get(SWITCHER_DELAY_KEY)
+                .and_then(|value| value.parse::<u64>().ok())
+                .filter(|delay| *delay > 0)
+                .unwrap_or(DEFAULT_SWITCHER_DELAY_MS),
+            ..Default::default()
+        }
+    }
+
+    /// Starts over with `sessions`, selecting the one used before the
+    /// current session.
+    pub fn open(&mut self, sessions: Vec<String>) {
+        self.sessions = sessions;
+        self.selected = 1.min(self.sessions.len().saturating_sub(1));
+    }
+
+    pub fn advance(&mut self, direction: i32) {
+        let len = self.sessions.len() as i32;
+        if len > 0 {
+            self.selected = (self.selected as i32 + direction).rem_euclid(len) as usize;
+        }
+    }
+
+    pub fn selected_session(&self) -> Option<&String> {
+        self.sessions.get(self.selected)
+    }
+
+    /// Pushes the commit back by the delay, returning the delay in seconds
+    /// for the timer that checks it.
+    pub fn postpone(&mut self, now_ms: u64) -> f64 {
+        self.commit_at = now_ms + self.delay_ms;
+        self.delay_ms as f64 / 1000.0
+    }
+
+    pub fn is_due(&self, now_ms: u64) -> bool {
+        now_ms >= self.commit_at
+    }
+
+    #[cfg(test)]
+    pub fn expire(&mut self) {
+        self.commit_at = 0;
+    }
+}
+
+/// The running sessions, most recently accessed first. The current session
+/// always leads, sessions never accessed through the plugin trail by name.
+pub fn recent_sessions(
+    hotbar_data: &HotbarData,
+    running: &[String],
+    current: Option<&str>,
+) -> Vec<String> {
+    let mut sessions: Vec<String> = running.to_vec();
+    sessions.sort_by_key(|name| {
+        let last_accessed = hotbar_data
+            .get_metadata(name)
+            .map_or(0, |metadata| metadata.last_accessed);
+        (
+            Some(name.as_str()) != current,
+            std::cmp::Reverse(last_accessed),
+        )
+    });
+    sessions
+}
@@ -1,14 +1,112 @@
-use crate::session_manager::{HotbarManager, ModalMode};
+use crate::bindings::{self, BindingFormat};
+use crate::dates;
+use crate::grouping::ListRow;
+use crate::logging::LogLevel;
+use crate::notify::{Level, Notification};
+use crate::persistence;
+use crate::screen::{self, Screen};
+use crate::session_manager::{HotbarManager, ModalMode, PendingChange, PermissionState};
+use crate::usage;
+
+// The preview pane is only shown when the modal is wide enough to fit it
+// next to the session list
+const MIN_COLS_FOR_PREVIEW: usize = 70;
+
+// Text starts two columns in from the left border and keeps a blank column
+// before the right one
+fn content_width(cols: usize) -> usize {
+    cols.saturating_sub(6)
+}
 
 pub fn render_hotbar_modal(manager: &mut HotbarManager, rows: usize, cols: usize) {
+    print!("{}", draw_hotbar_modal(manager, rows, cols).to_ansi());
+}
+
+/// Draws the modal into a screen of the pane's size without printing it.
+pub fn draw_hotbar_modal(manager: &mut HotbarManager, rows: usize, cols: usize) -> Screen {
+    let mut screen = Screen::new(rows, cols);
+    if manager.showing_toast {
+        if let Some(notification) = &manager.notification {
+            render_toast(&mut screen, notification, cols);
+        }
+        return screen;
+    }
+
+    match manager.modal_mode {
+        _ if manager.permissions == PermissionState::Denied => {
+            render_permission_screen(&mut screen, manager, rows, cols)
+        }
+        ModalMode::RestoreBackup => render_backup_screen(&mut screen, manager, rows, cols),
+        ModalMode::Stats => render_stats_screen(&mut screen, manager, rows, cols),
+        ModalMode::Bindings => render_bindings_screen(&mut screen, manager, rows, cols),
+        ModalMode::Log => render_log_screen(&mut screen, manager, rows, cols),
+        ModalMode::Switcher => render_switcher(&mut screen, manager, rows, cols),
+        _ => render_session_screen(&mut screen, manager, rows, cols),
+    }
+    // Every screen's box starts on the first row, so the notification goes
+    // on its top border
+    if let Some(notification) = &manager.notification
+        && rows > 0
+    {
+        let message = format!(" {} ", notification.message);
+        screen.print(
+            1,
+            3,
+            &screen::ellipsize(&message, content_width(cols)),
+            notification_style(notification.level),
+        );
+    }
+    screen
+}
+
+fn notification_style(level: Level) -> &'static str {
+    match level {
+        Level::Info => "1;32",
+        Level::Error => "1;31",
+    }
+}
+
+// The pane shown just for a notification: the message in a box of its own
+fn render_toast(screen: &mut Screen, notification: &Notification, cols: usize) {
+    render_box(screen, 1, 1, 3, cols);
+    screen.print(
+        2,
+        3,
+        &screen::ellipsize(&notification.message, content_width(cols)),
+        notification_style(notification.level),
+    );
+}
+
+fn render_session_screen(
+    screen: &mut Screen,
+    manager: &mut HotbarManager,
+    rows: usize,
+    cols: usize,
+) {
     // layout: title (2 lines) + sessions area + help (2 lines) + borders (2 lines)
     // Reserve space for title, help, and borders
     let reserved_lines = 7; // title (2) + help (2) + borders (2) + padding (1)
-    let max_session_area_height = rows.saturating_sub(reserved_lines).max(5);
+    let max_session_area_height = rows.saturating_sub(reserved_lines);
+
+    let preview_width = if cols >= MIN_COLS_FOR_PREVIEW {
+        cols * 2 / 5
+    } else {
+        0
+    };
+    let preview = if preview_width > 0 {
+        preview_lines(manager)
+    } else {
+        Vec::new()
+    };
 
     // Determine how many sessions can be displayed
-    let visible_session_count = manager.all_sessions.len().min(max_session_area_height);
-    let modal_height = (visible_session_count + reserved_lines).min(rows);
+    let visible_session_count = manager.rows.len().min(max_session_area_height);
+    let content_height = content_height(
+        manager,
+        visible_session_count.max(preview.len()),
+        max_session_area_height,
+    );
+    let modal_height = (content_height + reserved_lines).min(rows);
 
     // Adjust scroll position based on selection
     manager.adjust_scroll(visible_session_count);
@@ -17,71 +115,504 @@ pub fn render_hotbar_modal(manager: &mut HotbarManager, rows: usize, cols: usize
     let start_row = 1;
     let start_col = 1;
 
-    print!("\u{001b}[2J");
+    render_box(screen, start_row, start_col, modal_height, cols);
+    render_title(
+        screen,
+        start_row,
+        start_col,
+        cols,
+        manager,
+        visible_session_count,
+    );
+    render_scroll_indicators(
+        screen,
+        manager,
+        start_row + 2,
+        start_col,
+        cols - preview_width,
+        visible_session_count,
+    );
+    // With the preview, the list stops short of its separator instead of the border
+    let separator_col = (start_col + cols).saturating_sub(preview_width + 3);
+    let list_width = if preview_width > 0 {
+        separator_col.saturating_sub(start_col + 3)
+    } else {
+        content_width(cols)
+    };
+    render_sessions(
+        screen,
+        manager,
+        start_row + 3,
+        start_col + 2,
+        list_width,
+        visible_session_count,
+    );
+    if preview_width > 0 {
+        render_preview(
+            screen,
+            &preview,
+            start_row + 2,
+            separator_col,
+            preview_width.saturating_sub(3),
+            content_height + 2,
+        );
+    }
+    let help_row = start_row + 3 + content_height + 1;
+    if fits_above_border(help_row, 2, start_row, modal_height) {
+        render_help(
+            screen,
+            manager,
+            help_row,
+            start_col + 2,
+            content_width(cols),
+        );
+    }
+}
+
+fn render_backup_screen(screen: &mut Screen, manager: &HotbarManager, rows: usize, cols: usize) {
+    let reserved_lines = 7;
+    let max_list_height = rows.saturating_sub(reserved_lines);
+    // One header line plus one line per slot for the diff
+    let content_height = content_height(manager, manager.backups.len().max(6), max_list_height);
+    let modal_height = (content_height + reserved_lines).min(rows);
+
+    let start_row = 1;
+    let start_col = 1;
+
+    render_box(screen, start_row, start_col, modal_height, cols);
+    render_centered(screen, start_row + 1, start_col, cols, " Restore Backup ");
+
+    let list_row = start_row + 3;
+    let list_col = start_col + 2;
+
+    if manager.backups.is_empty() {
+        screen.print(list_row, list_col, "No backups yet", "2");
+    }
+
+    // Keep the selected backup in view when there are more than fit
+    let scroll_offset = (manager.backup_index + 1).saturating_sub(content_height);
+    for (display_row, (index, backup)) in manager
+        .backups
+        .iter()
+        .enumerate()
+        .skip(scroll_offset)
+        .take(content_height)
+        .enumerate()
+    {
+        let is_selected = index == manager.backup_index;
+        let prefix = if is_selected { "▶ " } else { "  " };
+        let line = format!("{}{}", prefix, dates::format_timestamp(backup.timestamp));
+        let line = screen::ellipsize(&line, content_width(cols));
+        let style = if is_selected { "1;36" } else { "" };
+        screen.print(list_row + display_row, list_col, &line, style);
+    }
+
+    if let Some(backup) = manager.get_selected_backup() {
+        let diff_col = list_col + 24;
+        let diff_width = (list_col + content_width(cols)).saturating_sub(diff_col);
+        screen.print(list_row, diff_col, "Slot  Current → Backup", "2");
+        for slot in 0..5 {
+            let current = manager.hotbar_data.get_session_at_slot(slot);
+            let restored = backup.data.get_session_at_slot(slot);
+            let describe =
+                |session: Option<&String>| session.cloned().unwrap_or_else(|| "—".to_string());
 
-    render_box(start_row, start_col, modal_height, cols);
-    render_title(start_row, start_col, cols, manager, visible_session_count);
-    render_scroll_indicators(manager, start_row + 2, start_col, cols, visible_session_count);
-    render_sessions(manager, start_row + 3, start_col + 2, visible_session_count);
-    render_help(manager, start_row + 3 + visible_session_count + 1, start_col + 2);
+            let row = list_row + 1 + slot;
+            if current == restored {
+                let line = format!(" {}    {}", slot + 1, describe(current));
+                screen.print(row, diff_col, &screen::ellipsize(&line, diff_width), "2");
+            } else {
+                // Both sides share what is left after the slot number and the arrow
+                let side_width = diff_width.saturating_sub(9) / 2;
+                let line = format!(
+                    " {}    {} → ",
+                    slot + 1,
+                    screen::ellipsize(&describe(current), side_width)
+                );
+                let end_col = screen.print(row, diff_col, &line, "");
+                let restored = screen::ellipsize(&describe(restored), side_width);
+                screen.print(row, end_col, &restored, "1;33");
+            }
+        }
+    }
+
+    if fits_above_border(list_row + content_height + 1, 1, start_row, modal_height) {
+        screen.print(
+            list_row + content_height + 1,
+            list_col,
+            &screen::ellipsize(
+                "↑/↓: Navigate  Enter: Restore  Esc/q: Back",
+                content_width(cols),
+            ),
+            "2",
+        );
+    }
 }
 
-fn render_box(row: usize, col: usize, height: usize, cols: usize) {
-    let width = cols.saturating_sub(2);
+fn render_stats_screen(screen: &mut Screen, manager: &HotbarManager, rows: usize, cols: usize) {
+    let today = dates::day_of(persistence::unix_now());
+    let stats = usage::session_stats(&manager.hotbar_data, today);
+
+    let reserved_lines = 7;
+    let max_list_height = rows.saturating_sub(reserved_lines);
+    // One header line plus one line per session
+    let content_height = content_height(manager, (stats.len() + 1).max(2), max_list_height);
+    let modal_height = (content_height + reserved_lines).min(rows);
 
-    print!("\u{001b}[{};{}H╭", row, col);
-    for _ in 0..width.saturating_sub(2) {
-        print!("─");
+    let start_row = 1;
+    let start_col = 1;
+
+    render_box(screen, start_row, start_col, modal_height, cols);
+    render_centered(screen, start_row + 1, start_col, cols, " Usage Stats ");
+
+    let list_row = start_row + 3;
+    let list_col = start_col + 2;
+    // Session names get whatever the three duration columns leave
+    let name_width = content_width(cols).saturating_sub(3 * 10).max(10);
+
+    let header = format!(
+        "{}{:>10}{:>10}{:>10}",
+        screen::pad("Session", name_width),
+        "Today",
+        "7 days",
+        "Total"
+    );
+    screen.print(list_row, list_col, &header, "2");
+
+    if stats.is_empty() {
+        screen.print(list_row + 1, list_col, "No focus time recorded yet", "2");
     }
-    print!("╮");
 
-    for i in 1..height - 1 {
-        print!("\u{001b}[{};{}H│", row + i, col);
-        print!("\u{001b}[{};{}H│", row + i, col + width - 1);
+    for (i, stat) in stats
+        .iter()
+        .take(content_height.saturating_sub(1))
+        .enumerate()
+    {
+        // Keep a blank column between a long name and the first duration
+        let name = screen::ellipsize(&stat.session, name_width - 1);
+        let line = format!(
+            "{}{:>10}{:>10}{:>10}",
+            screen::pad(&name, name_width),
+            dates::format_duration(stat.today),
+            dates::format_duration(stat.week),
+            dates::format_duration(stat.total)
+        );
+        screen.print(list_row + 1 + i, list_col, &line, "");
+    }
+
+    if fits_above_border(list_row + content_height + 1, 1, start_row, modal_height) {
+        screen.print(list_row + content_height + 1, list_col, "Esc/q: Back", "2");
+    }
+}
+
+fn render_bindings_screen(
+    screen: &mut Screen,
+    manager: &mut HotbarManager,
+    rows: usize,
+    cols: usize,
+) {
+    let snippet = bindings::generate(manager.bindings_format, bindings::DEFAULT_PLUGIN_ALIAS);
+    let lines: Vec<&str> = snippet.lines().collect();
+
+    let reserved_lines = 7;
+    let content_height = rows.saturating_sub(reserved_lines);
+    let modal_height = (content_height + reserved_lines).min(rows);
+    // The first content line lists the formats
+    let visible_lines = content_height.saturating_sub(1);
+    manager.bindings_scroll = manager
+        .bindings_scroll
+        .min(lines.len().saturating_sub(visible_lines));
+
+    let start_row = 1;
+    let start_col = 1;
+
+    render_box(screen, start_row, start_col, modal_height, cols);
+    let title = format!(" Keybindings: {} ", manager.bindings_format.name());
+    render_centered(screen, start_row + 1, start_col, cols, &title);
+
+    // The other formats, with the shown one highlighted
+    let list_row = start_row + 2;
+    let list_col = start_col + 2;
+    let mut tab_col = list_col;
+    for format in BindingFormat::ALL {
+        let style = if format == manager.bindings_format {
+            "1;36"
+        } else {
+            "2"
+        };
+        tab_col = screen.print(list_row, tab_col, format.name(), style) + 2;
+    }
+
+    for (i, line) in lines
+        .iter()
+        .skip(manager.bindings_scroll)
+        .take(visible_lines)
+        .enumerate()
+    {
+        let line = screen::ellipsize(line, content_width(cols));
+        screen.print(list_row + 2 + i, list_col, &line, "");
+    }
+
+    if fits_above_border(list_row + content_height + 2, 1, start_row, modal_height) {
+        screen.print(
+            list_row + content_height + 2,
+            list_col,
+            &screen::ellipsize("←/→: Format  ↑/↓: Scroll  Esc/q: Back", content_width(cols)),
+            "2",
+        );
+    }
+}
+
+fn render_permission_screen(
+    screen: &mut Screen,
+    manager: &HotbarManager,
+    rows: usize,
+    cols: usize,
+) {
+    let lines = [
+        "Zellij denied the permissions the hotbar asked for:",
+        "",
+        "  ChangeApplicationState  to switch sessions and tabs",
+        "  ReadApplicationState    to list sessions",
+        "  RunCommands             for hooks and project discovery",
+        "  ReadCliPipes            to answer `zellij pipe`",
+        "",
+        "Switches fail until they are granted.",
+    ];
+    let reserved_lines = 7;
+    let max_height = rows.saturating_sub(reserved_lines);
+    let content_height = content_height(manager, lines.len(), max_height);
+    let modal_height = (content_height + reserved_lines).min(rows);
+
+    let start_row = 1;
+    let start_col = 1;
+
+    render_box(screen, start_row, start_col, modal_height, cols);
+    render_centered(
+        screen,
+        start_row + 1,
+        start_col,
+        cols,
+        " Permissions Needed ",
+    );
+
+    let list_row = start_row + 3;
+    let list_col = start_col + 2;
+    for (i, line) in lines.iter().enumerate() {
+        screen.print(
+            list_row + i,
+            list_col,
+            &screen::ellipsize(line, content_width(cols)),
+            "",
+        );
+    }
+
+    if fits_above_border(list_row + content_height + 1, 1, start_row, modal_height) {
+        screen.print(
+            list_row + content_height + 1,
+            list_col,
+            &screen::ellipsize("r: Ask again  Esc/q: Close", content_width(cols)),
+            "2",
+        );
+    }
+}
+
+fn render_log_screen(screen: &mut Screen, manager: &mut HotbarManager, rows: usize, cols: usize) {
+    let reserved_lines = 7;
+    let content_height = rows.saturating_sub(reserved_lines);
+    let modal_height = (content_height + reserved_lines).min(rows);
+    let entry_count = manager.log.entries().len();
+    manager.log_scroll = manager
+        .log_scroll
+        .min(entry_count.saturating_sub(content_height));
+
+    let start_row = 1;
+    let start_col = 1;
+
+    render_box(screen, start_row, start_col, modal_height, cols);
+    let title = format!(" Log ({} and above) ", manager.log.level.label());
+    render_centered(screen, start_row + 1, start_col, cols, &title);
+
+    let list_row = start_row + 3;
+    let list_col = start_col + 2;
+
+    if entry_count == 0 {
+        screen.print(list_row, list_col, "Nothing logged yet", "2");
+    }
+
+    let width = content_width(cols);
+    for (i, entry) in manager
+        .log
+        .entries()
+        .skip(manager.log_scroll)
+        .take(content_height)
+        .enumerate()
+    {
+        let row = list_row + i;
+        // Only the time of day, the log doesn't outlive the session for long
+        let timestamp = dates::format_timestamp(entry.timestamp);
+        let col = screen.print(row, list_col, &timestamp[11..], "2");
+        let level_style = match entry.level {
+            LogLevel::Debug => "2",
+            LogLevel::Info => "",
+            LogLevel::Warn => "33",
+            LogLevel::Error => "31",
+        };
+        let col = screen.print(
+            row,
+            col + 1,
+            &screen::pad(entry.level.label(), 5),
+            level_style,
+        );
+        let message = entry.message.lines().collect::<Vec<_>>().join(" ");
+        let message_width = (list_col + width).saturating_sub(col + 1);
+        screen.print(
+            row,
+            col + 1,
+            &screen::ellipsize(&message, message_width),
+            "",
+        );
+    }
+
+    if fits_above_border(list_row + content_height + 1, 1, start_row, modal_height) {
+        screen.print(
+            list_row + content_height + 1,
+            list_col,
+            &screen::ellipsize("↑/↓: Scroll  Esc/q: Back", width),
+            "2",
+        );
+    }
+}
+
+// Just the recent sessions in a box that fits them, the title on its top
+// border, so the pane can be small
+fn render_switcher(screen: &mut Screen, manager: &HotbarManager, rows: usize, cols: usize) {
+    let switcher = &manager.switcher;
+    let visible_count = switcher.sessions.len().min(rows.saturating_sub(2));
+    let modal_height = if manager.geometry.fills_pane_height() {
+        rows
+    } else {
+        visible_count + 2
+    };
+    // Keep the selection in view when the pane is too short for every session
+    let offset = (switcher.selected + 1).saturating_sub(visible_count);
+
+    let start_row = 1;
+    let start_col = 1;
+
+    render_box(screen, start_row, start_col, modal_height, cols);
+    screen.print(
+        start_row,
+        start_col + 2,
+        &screen::ellipsize(" Recent Sessions ", content_width(cols)),
+        "1",
+    );
+
+    let width = content_width(cols);
+    for (i, session) in switcher
+        .sessions
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible_count)
+    {
+        let is_selected = i == switcher.selected;
+        let prefix = if is_selected { "▶ " } else { "  " };
+        let slot = match manager.get_slot_for_session(session) {
+            Some(slot) => format!("[{}] ", slot + 1),
+            None => "    ".to_string(),
+        };
+        let line = format!("{}{}{}", prefix, slot, session);
+        let style = if is_selected { "1;36" } else { "" };
+        screen.print(
+            start_row + 1 + i - offset,
+            start_col + 2,
+            &screen::ellipsize(&line, width),
+            style,
+        );
+    }
+}
+
+// A configured height sizes the pane to it, so the box fills the pane instead
+// of shrinking to its content and leaving the pane's bottom empty
+fn content_height(manager: &HotbarManager, needed: usize, max_height: usize) -> usize {
+    if manager.geometry.fills_pane_height() {
+        max_height
+    } else {
+        needed.min(max_height)
+    }
+}
+
+// Whether `lines` lines from `row` on stay above the bottom border of the box
+// starting at `start_row`, which a pane shorter than the layout wants cuts short
+fn fits_above_border(row: usize, lines: usize, start_row: usize, modal_height: usize) -> bool {
+    row + lines < start_row + modal_height
+}
+
+fn render_box(screen: &mut Screen, row: usize, col: usize, height: usize, cols: usize) {
+    let width = cols.saturating_sub(2);
+    // Too small for a top and a bottom border, or a left and a right one
+    if height < 2 || width < 2 {
+        return;
     }
 
-    print!("\u{001b}[{};{}H╰", row + height - 1, col);
-    for _ in 0..width.saturating_sub(2) {
-        print!("─");
+    let horizontal = "─".repeat(width - 2);
+    screen.print(row, col, &format!("╭{}╮", horizontal), "");
+
+    for i in 1..height - 1 {
+        screen.print(row + i, col, "│", "");
+        screen.print(row + i, col + width - 1, "│", "");
     }
-    print!("╯");
+
+    screen.print(row + height - 1, col, &format!("╰{}╯", horizontal), "");
+}
+
+// Centers a bold title on the top line inside the box, cut short when the box
+// is narrower than the title
+fn render_centered(screen: &mut Screen, row: usize, col: usize, cols: usize, title: &str) {
+    let width = cols.saturating_sub(2);
+    let inner_width = width.saturating_sub(2);
+    let title = screen::ellipsize(title, inner_width);
+    let title_col = col + 1 + (inner_width - screen::text_width(&title)) / 2;
+    screen.print(row, title_col, &title, "1");
 }
 
 fn render_title(
+    screen: &mut Screen,
     row: usize,
     col: usize,
     cols: usize,
     manager: &HotbarManager,
     visible_count: usize,
 ) {
-    let title = " Session Hotbar Manager ";
     let width = cols.saturating_sub(2);
-    let title_col = col + (width.saturating_sub(title.len())) / 2;
-    print!(
-        "\u{001b}[{};{}H\u{001b}[1m{}\u{001b}[0m",
-        row + 1,
-        title_col,
-        title
-    );
+    let title = " Session Hotbar Manager ";
+    render_centered(screen, row + 1, col, cols, title);
 
-    // Show scroll position on the right side of the title line
-    if manager.can_scroll_up() || manager.can_scroll_down(visible_count) {
-        let scroll_info = format!(
-            "{}/{}",
-            manager.selected_index + 1,
-            manager.all_sessions.len()
-        );
-        let info_col = col + width - scroll_info.len() - 3;
-        print!(
-            "\u{001b}[{};{}H\u{001b}[2m{}\u{001b}[0m",
+    // Show the active filter on the left side of the title line
+    if !manager.filter.is_empty() && manager.modal_mode != ModalMode::Filter {
+        // Keep the filter clear of the centered title
+        let filter = format!("/{}", manager.filter);
+        let filter_width = (width.saturating_sub(screen::text_width(title)) / 2).saturating_sub(2);
+        screen.print(
             row + 1,
-            info_col,
-            scroll_info
+            col + 2,
+            &screen::ellipsize(&filter, filter_width),
+            "33",
         );
     }
+
+    // Show scroll position on the right side of the title line
+    if manager.can_scroll_up() || manager.can_scroll_down(visible_count) {
+        let scroll_info = format!("{}/{}", manager.selected_index + 1, manager.rows.len());
+        if let Some(offset) = width.checked_sub(screen::text_width(&scroll_info) + 3) {
+            screen.print(row + 1, col + offset, &scroll_info, "2");
+        }
+    }
 }
 
 fn render_scroll_indicators(
+    screen: &mut Screen,
     manager: &HotbarManager,
     row: usize,
     col: usize,
@@ -89,89 +620,601 @@ fn render_scroll_indicators(
     visible_count: usize,
 ) {
     let width = cols.saturating_sub(2);
-    let indicator_col = col + width - 2;
+    let indicator_col = (col + width).saturating_sub(2);
 
     // Show up arrow if can scroll up
     if manager.can_scroll_up() {
-        print!(
-            "\u{001b}[{};{}H\u{001b}[1;33m↑\u{001b}[0m",
-            row,
-            indicator_col
-        );
+        screen.print(row, indicator_col, "↑", "1;33");
     }
 
     // Show down arrow if can scroll down (at the bottom of the session area)
     if manager.can_scroll_down(visible_count) {
-        print!(
-            "\u{001b}[{};{}H\u{001b}[1;33m↓\u{001b}[0m",
-            row + visible_count + 1,
-            indicator_col
-        );
+        screen.print(row + visible_count + 1, indicator_col, "↓", "1;33");
     }
 }
 
-fn render_sessions(manager: &HotbarManager, row: usize, col: usize, visible_height: usize) {
-    let end_index = (manager.scroll_offset + visible_height).min(manager.all_sessions.len());
+fn render_sessions(
+    screen: &mut Screen,
+    manager: &HotbarManager,
+    row: usize,
+    col: usize,
+    width: usize,
+    visible_height: usize,
+) {
+    let end_index = (manager.scroll_offset + visible_height).min(manager.rows.len());
+
+    for (display_row, row_index) in (manager.scroll_offset..end_index).enumerate() {
+        let is_selected = row_index == manager.selected_index;
+        let prefix = if is_selected { "▶ " } else { "  " };
 
-    for (display_row, session_index) in (manager.scroll_offset..end_index).enumerate() {
-        let session_name = &manager.all_sessions[session_index];
-        let is_selected = session_index == manager.selected_index;
+        let (session_name, label, grouped) = match &manager.rows[row_index] {
+            ListRow::Group {
+                name,
+                count,
+                collapsed,
+            } => {
+                let fold = if *collapsed { "▸" } else { "▾" };
+                let style = if is_selected { "1;36" } else { "1;33" };
+                let count = format!(" ({})", count);
+                let name_width = width.saturating_sub(4 + screen::text_width(&count));
+                let line = format!(
+                    "{}{} {}{}",
+                    prefix,
+                    fold,
+                    screen::ellipsize(name, name_width),
+                    count
+                );
+                screen.print(row + display_row, col, &line, style);
+                continue;
+            }
+            ListRow::Session {
+                name,
+                label,
+                grouped,
+            } => (name, label, *grouped),
+        };
         let is_current = manager.active_session.as_ref() == Some(session_name);
+        let is_marked = manager.is_marked(session_name);
+        let indent = if grouped { "  " } else { "" };
+        // Marks share the selection marker's column pair
+        let prefix = match (is_selected, is_marked) {
+            (true, true) => "▶●",
+            (false, true) => " ●",
+            _ => prefix,
+        };
 
-        let prefix = if is_selected { "▶ " } else { "  " };
+        let slot_indicator = match manager.get_slot_for_session(session_name) {
+            Some(slot) if manager.hotbar_data.is_locked(slot) => format!("[{}]🔒 ", slot + 1),
+            Some(slot) => format!("[{}]   ", slot + 1),
+            None => "      ".to_string(),
+        };
 
-        let slot_indicator = if let Some(slot) = manager.get_slot_for_session(session_name) {
-            format!("[{}] ", slot + 1)
+        let current_indicator = if is_current {
+            " (current)"
+        } else if !manager.is_running(session_name) {
+            " (not started)"
         } else {
-            "    ".to_string()
+            ""
         };
 
-        let current_indicator = if is_current { " (current)" } else { "" };
+        // The label gives way first, so the badge and the suffix always fit
+        let fixed = format!("{}{}{}", prefix, indent, slot_indicator);
+        let label_width = width
+            .saturating_sub(screen::text_width(&fixed) + screen::text_width(current_indicator));
+        let label = screen::ellipsize(label, label_width);
+        let line = format!("{}{}{}", fixed, label, current_indicator);
 
-        let line = format!(
-            "{}{}{}{}",
-            prefix, slot_indicator, session_name, current_indicator
-        );
-
-        if is_selected {
-            print!(
-                "\u{001b}[{};{}H\u{001b}[1;36m{}\u{001b}[0m",
-                row + display_row,
-                col,
-                line
-            );
+        let style = if is_selected {
+            "1;36"
+        } else if is_marked {
+            "35"
         } else if is_current {
-            print!(
-                "\u{001b}[{};{}H\u{001b}[1m{}\u{001b}[0m",
-                row + display_row,
-                col,
-                line
-            );
+            "1"
         } else {
-            print!("\u{001b}[{};{}H{}", row + display_row, col, line);
+            ""
+        };
+        let end_col = screen.print(row + display_row, col, &line, style);
+
+        // Tags and note trail the name, dimmed
+        if let Some(metadata) = manager.hotbar_data.get_metadata(session_name) {
+            let mut details = String::new();
+            for tag in &metadata.tags {
+                details.push_str(&format!(" #{}", tag));
+            }
+            if !metadata.note.is_empty() {
+                details.push_str(&format!(" — {}", metadata.note));
+            }
+            let details_width = (col + width).saturating_sub(end_col);
+            if !details.is_empty() && details_width > 1 {
+                let details = screen::ellipsize(&details, details_width);
+                screen.print(row + display_row, end_col, &details, "2");
+            }
+        }
+    }
+}
+
+struct PreviewLine {
+    text: String,
+    // SGR parameters applied to the whole line
+    style: &'static str,
+}
+
+impl PreviewLine {
+    fn new(text: impl Into<String>, style: &'static str) -> Self {
+        Self {
+            text: text.into(),
+            style,
         }
     }
 }
 
-fn render_help(manager: &HotbarManager, row: usize, col: usize) {
+fn preview_lines(manager: &HotbarManager) -> Vec<PreviewLine> {
+    let Some(session_name) = manager.get_selected_session() else {
+        return Vec::new();
+    };
+
+    let mut lines = vec![PreviewLine::new(session_name.as_str(), "1")];
+
+    let Some(session) = manager.session_info(session_name) else {
+        if let Some(project) = manager.project_for(session_name) {
+            lines.push(PreviewLine::new("Not started", "2"));
+            lines.push(PreviewLine::new(
+                format!("Path: {}", project.path.display()),
+                "",
+            ));
+            if let Some(branch) = &project.branch {
+                lines.push(PreviewLine::new(format!("Branch: {}", branch), ""));
+            }
+        }
+        return lines;
+    };
+
+    let layout = session
+        .tabs
+        .iter()
+        .find(|tab| tab.active)
+        .and_then(|tab| tab.active_swap_layout_name.as_deref())
+        .unwrap_or("default");
+    lines.push(PreviewLine::new(
+        format!(
+            "Clients: {}  Tabs: {}",
+            session.connected_clients,
+            session.tabs.len()
+        ),
+        "",
+    ));
+    lines.push(PreviewLine::new(format!("Layout: {}", layout), ""));
+
+    for tab in &session.tabs {
+        let marker = if tab.active { "▸" } else { " " };
+        lines.push(PreviewLine::new(
+            format!("{} {}: {}", marker, tab.position + 1, tab.name),
+            if tab.active { "1" } else { "" },
+        ));
+
+        let panes = session.panes.panes.get(&tab.position);
+        for pane in panes.into_iter().flatten() {
+            // Plugin panes are mostly tab/status bars, which tell sessions apart poorly
+            if pane.is_plugin || pane.is_suppressed {
+                continue;
+            }
+            let label = pane.terminal_command.as_ref().unwrap_or(&pane.title);
+            lines.push(PreviewLine::new(format!("    {}", label), "2"));
+        }
+    }
+
+    lines
+}
+
+fn render_preview(
+    screen: &mut Screen,
+    lines: &[PreviewLine],
+    row: usize,
+    separator_col: usize,
+    width: usize,
+    height: usize,
+) {
+    for i in 0..height {
+        screen.print(row + i, separator_col, "│", "2");
+    }
+
+    // Leave a blank row under the title line, mirroring the session list
+    for (i, line) in lines.iter().take(height.saturating_sub(2)).enumerate() {
+        let text = screen::ellipsize(&line.text, width);
+        screen.print(row + 1 + i, separator_col + 2, &text, line.style);
+    }
+}
+
+fn render_help(screen: &mut Screen, manager: &HotbarManager, row: usize, col: usize, width: usize) {
+    let slot_contents = |slot: usize| {
+        manager
+            .hotbar_data
+            .get_session_at_slot(slot)
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let prompt = |label: String| {
+        vec![
+            format!("{}{}█", label, manager.input),
+            "Enter: Save  Esc: Cancel".to_string(),
+        ]
+    };
+
+    let confirm = |summary: String| vec![summary, "y: Confirm  Any other key: Cancel".to_string()];
+
+    let slot_list = |slots: &[usize]| {
+        let numbers: Vec<String> = slots.iter().map(|slot| (slot + 1).to_string()).collect();
+        match numbers.len() {
+            1 => format!("slot {}", numbers[0]),
+            _ => format!("slots {}", numbers.join(", ")),
+        }
+    };
+
     let help_text = if manager.modal_mode == ModalMode::Move {
         vec![
-            "Move Mode - Select destination slot:",
-            "↑/↓: Navigate  Enter: Confirm  Esc: Cancel",
+            "Move Mode - Select destination slot:".to_string(),
+            "↑/↓: Navigate  Enter: Confirm  Esc: Cancel".to_string(),
+        ]
+    } else if manager.modal_mode == ModalMode::Filter {
+        vec![
+            format!("/{}█", manager.input),
+            "Type to filter, #tag matches tags  Enter: Apply  Esc: Clear".to_string(),
+        ]
+    } else if let ModalMode::EditNote(session) = &manager.modal_mode {
+        prompt(format!("Note for '{}': ", session))
+    } else if let ModalMode::EditTags(session) = &manager.modal_mode {
+        prompt(format!("Tags for '{}': ", session))
+    } else if let ModalMode::Confirm(change) = &manager.modal_mode {
+        match change {
+            PendingChange::Assign { slot, session } => vec![
+                format!(
+                    "Slot {} is locked and holds '{}'",
+                    slot + 1,
+                    slot_contents(*slot)
+                ),
+                format!("y: Replace with '{}'  Any other key: Cancel", session),
+            ],
+            PendingChange::Remove { slot } => vec![
+                format!("Slot {} is locked", slot + 1),
+                format!(
+                    "y: Remove '{}' from hotbar  Any other key: Cancel",
+                    slot_contents(*slot)
+                ),
+            ],
+            PendingChange::AssignMarked {
+                assignments,
+                left_out,
+            } => {
+                let mut summary = assignments
+                    .iter()
+                    .map(|(slot, session)| format!("{} → {}", session, slot + 1))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if !left_out.is_empty() {
+                    summary.push_str(&format!("; no free slot for {}", left_out.join(", ")));
+                }
+                confirm(format!("Assign {}", summary))
+            }
+            PendingChange::RemoveMarked(slots) => {
+                let summary = slots
+                    .iter()
+                    .map(|(slot, session)| {
+                        let lock = if manager.hotbar_data.is_locked(*slot) {
+                            "🔒"
+                        } else {
+                            ""
+                        };
+                        format!("{} ({}{})", session, slot + 1, lock)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                confirm(format!("Remove from hotbar: {}", summary))
+            }
+            PendingChange::TagMarked { sessions, tags } => confirm(format!(
+                "Tag {} with '{}'",
+                sessions.join(", "),
+                tags.trim()
+            )),
+            PendingChange::KillSessions(sessions) => {
+                confirm(format!("Kill {}", sessions.join(", ")))
+            }
+            PendingChange::Undo(slots) => {
+                confirm(format!("Undo changes locked {}", slot_list(slots)))
+            }
+            PendingChange::Redo(slots) => {
+                confirm(format!("Redo changes locked {}", slot_list(slots)))
+            }
+            PendingChange::RestoreBackup {
+                timestamp, locked, ..
+            } => confirm(format!(
+                "Restoring the backup from {} changes locked {}",
+                dates::format_timestamp(*timestamp),
+                slot_list(locked)
+            )),
+        }
+    } else if manager.modal_mode == ModalMode::TagMarked {
+        prompt(format!(
+            "Tags to add to {} sessions: ",
+            manager.marked.len()
+        ))
+    } else if !manager.marked.is_empty() {
+        vec![
+            format!(
+                "{} marked  1-5: Assign from slot  x: Remove  t: Tag  K: Kill",
+                manager.marked.len()
+            ),
+            "Space: Mark  Esc: Clear marks  Enter: Switch".to_string(),
         ]
     } else {
         vec![
-            "1-5: Assign to hotbar slot  x: Remove from hotbar",
-            "↑/↓: Navigate  Enter: Switch session  Esc/q: Close",
+            "Space: Mark  1-5: Assign  x: Remove  p: Lock  n/t: Note/Tags  /: Filter".to_string(),
+            "Enter: Switch  g: Group  u/^r: Undo/redo  b/s/k/L: Backups/Stats/Keys/Log".to_string(),
         ]
     };
 
     for (i, text) in help_text.iter().enumerate() {
-        print!(
-            "\u{001b}[{};{}H\u{001b}[2m{}\u{001b}[0m",
-            row + i,
-            col,
-            text
+        screen.print(row + i, col, &screen::ellipsize(text, width), "2");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Size;
+    use crate::notify::Verbosity;
+    use crate::session_manager::tests::{session, test_manager};
+    use std::path::PathBuf;
+    use zellij_tile::prelude::PermissionStatus;
+
+    // Compares against `src/snapshots/<name>.txt`. Run the tests with
+    // UPDATE_SNAPSHOTS=1 to write the snapshots after an intended change.
+    fn assert_snapshot(name: &str, screen: &Screen) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/snapshots")
+            .join(format!("{}.txt", name));
+        let actual = screen.to_text();
+
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &actual).unwrap();
+            return;
+        }
+
+        let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "missing snapshot {}, run with UPDATE_SNAPSHOTS=1",
+                path.display()
+            )
+        });
+        assert_eq!(actual, expected, "snapshot {} changed", name);
+    }
+
+    fn manager_with_sessions(test_name: &str, names: &[&str]) -> HotbarManager {
+        let (mut manager, _host) = test_manager(test_name);
+        manager.update_session_list(names.iter().map(|name| session(name)).collect());
+        manager
+    }
+
+    #[test]
+    fn renders_at_many_sizes() {
+        let sizes = [
+            (0, 0),
+            (1, 1),
+            (1, 30),
+            (2, 4),
+            (3, 12),
+            (8, 30),
+            (12, 50),
+            (24, 80),
+            (30, 120),
+        ];
+        for (rows, cols) in sizes {
+            let mut manager =
+                manager_with_sessions(&format!("ui_size_{}x{}", rows, cols), &["api", "docs"]);
+            manager.set_active_session("api");
+            manager.assign_selected_to_slot(0).unwrap();
+
+            let screen = draw_hotbar_modal(&mut manager, rows, cols);
+            // Nothing may be drawn over the bottom border, however short the pane
+            let text = screen.to_text();
+            if let Some(bottom) = text.lines().map(str::trim).rfind(|line| !line.is_empty()) {
+                assert!(
+                    bottom.starts_with('╰')
+                        && bottom.ends_with('╯')
+                        && bottom.trim_matches(['╰', '╯']).chars().all(|c| c == '─'),
+                    "{}x{} has no intact bottom border: {}",
+                    rows,
+                    cols,
+                    bottom
+                );
+            }
+            assert_snapshot(&format!("sessions_{}x{}", rows, cols), &screen);
+        }
+    }
+
+    #[test]
+    fn renders_long_session_names() {
+        let long_name = "a-session-with-a-name-far-longer-than-the-modal-is-wide";
+        let mut manager = manager_with_sessions("ui_long_names", &[long_name, "short"]);
+        manager.navigate_sessions(1);
+        manager.assign_selected_to_slot(1).unwrap();
+
+        let screen = draw_hotbar_modal(&mut manager, 14, 40);
+        assert_snapshot("long_session_names", &screen);
+    }
+
+    #[test]
+    fn renders_wide_session_names() {
+        let names = [
+            "日本語のプロジェクト名前がとても長いセッション",
+            "🚀-launch-🚀-rocket-🚀-party",
+            "café",
+        ];
+        let mut manager = manager_with_sessions("ui_wide_names", &names);
+        manager.set_active_session(names[0]);
+        manager.assign_selected_to_slot(0).unwrap();
+        manager.toggle_selected_slot_lock().unwrap();
+        manager.navigate_sessions(1);
+        manager.assign_selected_to_slot(1).unwrap();
+        manager.hotbar_data.set_tags("café", "work coffee-break");
+        manager
+            .hotbar_data
+            .set_note("café", "the long note that cannot fit");
+
+        assert_snapshot("wide_names_40", &draw_hotbar_modal(&mut manager, 14, 40));
+        assert_snapshot("wide_names_90", &draw_hotbar_modal(&mut manager, 14, 90));
+    }
+
+    #[test]
+    fn renders_long_names_in_stats() {
+        let (mut manager, _host) = test_manager("ui_stats_names");
+        manager.hotbar_data.add_focus_time(
+            "日本語のプロジェクト名前がとても長いセッション",
+            0,
+            3600,
+        );
+        manager.hotbar_data.add_focus_time("api", 0, 60);
+        manager.modal_mode = ModalMode::Stats;
+
+        assert_snapshot("stats_long_names", &draw_hotbar_modal(&mut manager, 12, 60));
+    }
+
+    #[test]
+    fn renders_empty_list() {
+        let mut manager = manager_with_sessions("ui_empty", &[]);
+
+        assert_snapshot("empty_list", &draw_hotbar_modal(&mut manager, 14, 60));
+        assert_snapshot(
+            "empty_list_preview",
+            &draw_hotbar_modal(&mut manager, 14, 90),
         );
     }
+
+    #[test]
+    fn renders_scrolled_list() {
+        let names: Vec<String> = (1..=20).map(|i| format!("session-{:02}", i)).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let mut manager = manager_with_sessions("ui_scrolled", &names);
+        for _ in 0..12 {
+            manager.navigate_sessions(1);
+        }
+
+        assert_snapshot("scrolled_list", &draw_hotbar_modal(&mut manager, 14, 50));
+    }
+
+    #[test]
+    fn renders_bindings_screen() {
+        let mut manager = manager_with_sessions("ui_bindings", &[]);
+        manager.open_bindings();
+        assert_snapshot("bindings_kdl", &draw_hotbar_modal(&mut manager, 16, 80));
+
+        manager.cycle_bindings_format(1);
+        for _ in 0..100 {
+            manager.scroll_bindings(1);
+        }
+        assert_snapshot(
+            "bindings_nvim_end",
+            &draw_hotbar_modal(&mut manager, 16, 80),
+        );
+    }
+
+    #[test]
+    fn renders_notifications() {
+        let mut manager = manager_with_sessions("ui_notifications", &["api", "docs"]);
+        manager.notifications.verbosity = Verbosity::All;
+        manager.show_ui();
+        manager.assign_selected_to_slot(0).unwrap();
+        assert_snapshot(
+            "notification_in_modal",
+            &draw_hotbar_modal(&mut manager, 12, 60),
+        );
+
+        manager.hide_ui();
+        let result = manager.switch_to_slot(3);
+        manager.report(result);
+        assert!(manager.showing_toast);
+        assert_snapshot(
+            "notification_toast",
+            &draw_hotbar_modal(&mut manager, 6, 40),
+        );
+    }
+
+    #[test]
+    fn renders_log_screen() {
+        let mut manager = manager_with_sessions("ui_log", &["api"]);
+        manager.log.debug("hidden at the default level");
+        manager.log.info("Switched to session: api");
+        manager
+            .log
+            .warn("Invalid pattern for slot_1: a multi-line\nerror");
+        manager.notify(Level::Error, "No session saved in slot 3".to_string());
+        // Entries are timestamped with the current time
+        for entry in manager.log.entries_mut() {
+            entry.timestamp = 45_296;
+        }
+        manager.open_log();
+
+        assert_snapshot("log", &draw_hotbar_modal(&mut manager, 12, 60));
+    }
+
+    #[test]
+    fn renders_switcher() {
+        let mut manager = manager_with_sessions("ui_switcher", &["api", "docs", "web"]);
+        manager
+            .hotbar_data
+            .set_session_at_slot(1, Some("api".to_string()));
+        manager.switcher.open(vec![
+            "web".to_string(),
+            "api".to_string(),
+            "docs".to_string(),
+        ]);
+        manager.modal_mode = ModalMode::Switcher;
+
+        assert_snapshot("switcher", &draw_hotbar_modal(&mut manager, 8, 40));
+    }
+
+    #[test]
+    fn fills_a_pane_of_configured_height() {
+        let mut manager = manager_with_sessions("ui_geometry", &["api", "docs"]);
+        manager.geometry.height = Some(Size::Percent(80));
+        assert_snapshot("fixed_height", &draw_hotbar_modal(&mut manager, 16, 50));
+    }
+
+    #[test]
+    fn renders_marked_sessions() {
+        let mut manager = manager_with_sessions("ui_marks", &["api", "docs", "web"]);
+        manager.marked = vec!["api".to_string(), "web".to_string()];
+        manager.selected_index = 2;
+        assert_snapshot("marked", &draw_hotbar_modal(&mut manager, 12, 70));
+
+        manager.assign_marked_from(1);
+        assert_snapshot(
+            "marked_confirm_assign",
+            &draw_hotbar_modal(&mut manager, 12, 70),
+        );
+    }
+
+    #[test]
+    fn renders_denied_permissions() {
+        let mut manager = manager_with_sessions("ui_permissions", &["api"]);
+        manager.set_permission_status(PermissionStatus::Denied);
+        manager.notification = None;
+
+        assert_snapshot(
+            "permissions_denied",
+            &draw_hotbar_modal(&mut manager, 16, 70),
+        );
+    }
+
+    #[test]
+    fn renders_empty_backup_and_stats_screens() {
+        let mut manager = manager_with_sessions("ui_screens", &["api"]);
+
+        manager.modal_mode = ModalMode::RestoreBackup;
+        assert_snapshot("backups_empty", &draw_hotbar_modal(&mut manager, 14, 60));
+
+        manager.modal_mode = ModalMode::Stats;
+        assert_snapshot("stats_empty", &draw_hotbar_modal(&mut manager, 14, 60));
+    }
 }
@@ -0,0 +1,231 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::dates;
+use crate::persistence::HotbarData;
+
+const DEFAULT_REPORT_DAYS: u64 = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+/// What a `usage_report` pipe asks for, read from its args, e.g.
+/// `--args "format=json,from=2025-01-01,to=2025-01-31"`. Without a range the
+/// report covers the last 7 days.
+pub struct ReportOptions {
+    pub format: ReportFormat,
+    pub from_day: u64,
+    pub to_day: u64,
+}
+
+impl ReportOptions {
+    pub fn from_args(args: &BTreeMap<String, String>, today: u64) -> Result<Self, String> {
+        let format = match args.get("format").map(String::as_str) {
+            None | Some("csv") => ReportFormat::Csv,
+            Some("json") => ReportFormat::Json,
+            Some(other) => return Err(format!("Unknown report format: {}", other)),
+        };
+
+        let parse = |key: &str| -> Result<Option<u64>, String> {
+            args.get(key)
+                .map(|date| dates::parse_day(date).ok_or(format!("Invalid {} date: {}", key, date)))
+                .transpose()
+        };
+        let to_day = parse("to")?.unwrap_or(today);
+        let from_day =
+            parse("from")?.unwrap_or_else(|| to_day.saturating_sub(DEFAULT_REPORT_DAYS - 1));
+        if from_day > to_day {
+            return Err("Report range starts after it ends".to_string());
+        }
+
+        Ok(Self {
+            format,
+            from_day,
+            to_day,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct ReportRow {
+    date: String,
+    session: String,
+    seconds: u64,
+}
+
+/// Renders focus time per session and day within the range, one row per
+/// session and day with any time recorded.
+pub fn report(data: &HotbarData, options: &ReportOptions) -> String {
+    let mut rows: Vec<(u64, &String, u64)> = data
+        .focus_time
+        .iter()
+        .flat_map(|(session, days)| {
+            days.iter()
+                .filter(|(day, _)| (options.from_day..=options.to_day).contains(*day))
+                .map(move |(day, seconds)| (*day, session, *seconds))
+        })
+        .collect();
+    rows.sort();
+
+    let rows = rows.into_iter().map(|(day, session, seconds)| ReportRow {
+        date: dates::format_day(day),
+        session: session.clone(),
+        seconds,
+    });
+
+    match options.format {
+        ReportFormat::Csv => {
+            let mut csv = String::from("date,session,seconds\n");
+            for row in rows {
+                csv.push_str(&format!(
+                    "{},{},{}\n",
+                    row.date,
+                    csv_field(&row.session),
+                    row.seconds
+                ));
+            }
+            csv
+        }
+        ReportFormat::Json => {
+            let rows: Vec<ReportRow> = rows.collect();
+            serde_json::to_string_pretty(&rows).unwrap_or_default() + "\n"
+        }
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A session's focus time today, over the last 7 days and overall.
+pub struct SessionStats {
+    pub session: String,
+    pub today: u64,
+    pub week: u64,
+    pub total: u64,
+}
+
+/// Stats for every tracked session, most used first.
+pub fn session_stats(data: &HotbarData, today: u64) -> Vec<SessionStats> {
+    let week_start = today.saturating_sub(DEFAULT_REPORT_DAYS - 1);
+    let mut stats: Vec<SessionStats> = data
+        .focus_time
+        .keys()
+        .map(|session| SessionStats {
+            session: session.clone(),
+            today: data.focus_seconds(session, today, today),
+            week: data.focus_seconds(session, week_start, today),
+            total: data.focus_seconds(session, 0, u64::MAX),
+        })
+        .collect();
+    stats.sort_by(|a, b| {
+        b.total
+            .cmp(&a.total)
+            .then_with(|| a.session.cmp(&b.session))
+    });
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn args(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn reads_report_options_from_args() {
+        let today = dates::parse_day("2025-03-10").unwrap();
+        let options = ReportOptions::from_args(&args(&[]), today).unwrap();
+        assert_eq!(options.format, ReportFormat::Csv);
+        assert_eq!((options.from_day, options.to_day), (today - 6, today));
+
+        let options = ReportOptions::from_args(
+            &args(&[
+                ("format", "json"),
+                ("from", "2025-01-01"),
+                ("to", "2025-01-31"),
+            ]),
+            today,
+        )
+        .unwrap();
+        assert_eq!(options.format, ReportFormat::Json);
+        assert_eq!(dates::format_day(options.from_day), "2025-01-01");
+        assert_eq!(dates::format_day(options.to_day), "2025-01-31");
+    }
+
+    #[test]
+    fn rejects_invalid_report_args() {
+        for (pairs, error) in [
+            (vec![("format", "xml")], "Unknown report format: xml"),
+            (
+                vec![("from", "2025-13-01")],
+                "Invalid from date: 2025-13-01",
+            ),
+            (vec![("to", "yesterday")], "Invalid to date: yesterday"),
+            (
+                vec![("from", "2025-02-01"), ("to", "2025-01-31")],
+                "Report range starts after it ends",
+            ),
+        ] {
+            let result = ReportOptions::from_args(&args(&pairs), 20_000);
+            assert_eq!(result.err().as_deref(), Some(error));
+        }
+    }
+
+    #[test]
+    fn quotes_csv_fields_that_need_it() {
+        assert_eq!(csv_field("api"), "api");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn reports_focus_time_within_the_range() {
+        let day = dates::parse_day("2025-01-31").unwrap();
+        let mut data = HotbarData::default();
+        data.focus_time.insert(
+            "web,shop".to_string(),
+            HashMap::from([(day - 1, 60), (day, 120), (day + 1, 5)]),
+        );
+        data.focus_time
+            .insert("api".to_string(), HashMap::from([(day, 30)]));
+        let mut options = ReportOptions {
+            format: ReportFormat::Csv,
+            from_day: day - 1,
+            to_day: day,
+        };
+
+        assert_eq!(
+            report(&data, &options),
+            "date,session,seconds\n\
+             2025-01-30,\"web,shop\",60\n\
+             2025-01-31,api,30\n\
+             2025-01-31,\"web,shop\",120\n"
+        );
+
+        options.format = ReportFormat::Json;
+        options.from_day = day;
+        let rows: serde_json::Value = serde_json::from_str(&report(&data, &options)).unwrap();
+        assert_eq!(
+            rows,
+            serde_json::json!([
+                { "date": "2025-01-31", "session": "api", "seconds": 30 },
+                { "date": "2025-01-31", "session": "web,shop", "seconds": 120 },
+            ])
+        );
+    }
+}